@@ -0,0 +1,398 @@
+// Parsing of the `#[builder(...)]` attributes found on the container and on its fields.
+//
+// Problems are not returned one at a time. Instead every malformed key across all of the
+// attributes is pushed into `Errors`, such that the caller gets to fix all of them at once.
+
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, Ident, Lit, LitStr};
+
+// Keys accepted by `#[builder(...)]` on a field
+const FIELD_KEYS: &[&str] = &["each", "setter", "extend", "rename", "default_with"];
+// Keys accepted inside `#[builder(each(...))]`
+const EACH_KEYS: &[&str] = &["name", "into"];
+// Keys accepted inside `#[builder(setter(...))]`
+const SETTER_KEYS: &[&str] = &["skip"];
+// Keys accepted by `#[builder(...)]` on the struct or enum
+const CONTAINER_KEYS: &[&str] = &["rename_all", "prefix"];
+
+// Accumulates errors into a single `syn::Error`, which reports each of them when turned into a
+// compile error
+#[derive(Default)]
+pub(crate) struct Errors {
+    combined: Option<syn::Error>,
+}
+
+impl Errors {
+    pub(crate) fn push(&mut self, error: syn::Error) {
+        match &mut self.combined {
+            Some(combined) => combined.combine(error),
+            None => self.combined = Some(error),
+        }
+    }
+
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        match self.combined {
+            Some(combined) => Err(combined),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) struct EachSetter {
+    pub(crate) name: Ident,
+    // Whether the setter accepts `impl Into<T>` instead of the element type `T`
+    pub(crate) into: bool,
+}
+
+// Options given to a single field through `#[builder(...)]`
+#[derive(Default)]
+pub(crate) struct FieldOptions {
+    // One-at-a-time setter from `#[builder(each = "...")]` or `#[builder(each(...))]`
+    pub(crate) each: Option<EachSetter>,
+    // Set by `#[builder(setter(skip))]`, drops the setter for the entire field
+    pub(crate) skip_setter: bool,
+    // Set by `#[builder(extend)]`, adds a `<field>_extend` setter taking any `IntoIterator`
+    pub(crate) extend: bool,
+    // Closure from `#[builder(default_with = "...")]` along with the literal it came from
+    pub(crate) default_with: Option<(Expr, LitStr)>,
+    // Name of the entire field setter from `#[builder(rename = "...")]`
+    pub(crate) rename: Option<Ident>,
+}
+
+impl FieldOptions {
+    // Parses the options of a field. `repeated` tells whether the field is a `Vec<T>`, which is
+    // required by the collection options.
+    pub(crate) fn parse(attrs: &[Attribute], repeated: bool, errors: &mut Errors) -> Self {
+        let mut options = FieldOptions::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+            let result = attr.parse_nested_meta(|meta| {
+                match key_name(&meta).as_str() {
+                    "each" => {
+                        // Either the short `each = "arg"` form or `each(name = "arg", into)`
+                        let each = if meta.input.peek(syn::Token![=]) {
+                            parse_ident_value(&meta, errors)?.map(|name| EachSetter { name, into: false })
+                        } else {
+                            parse_each(&meta, errors)?
+                        };
+
+                        if !repeated {
+                            errors.push(meta.error("`each` can only be used on a `Vec<T>` field"));
+                        } else if each.is_some() {
+                            options.each = each;
+                        }
+                    }
+                    "setter" => {
+                        meta.parse_nested_meta(|setter_meta| {
+                            match key_name(&setter_meta).as_str() {
+                                "skip" => options.skip_setter = true,
+                                _ => {
+                                    errors.push(unknown_key(&setter_meta, "setter(...)", SETTER_KEYS));
+                                    skip_value(&setter_meta)?;
+                                }
+                            }
+                            Ok(())
+                        })?;
+                    }
+                    "extend" => {
+                        if !repeated {
+                            errors.push(meta.error("`extend` can only be used on a `Vec<T>` field"));
+                        }
+                        options.extend = true;
+                    }
+                    "rename" => {
+                        if let Some(rename) = parse_ident_value(&meta, errors)? {
+                            options.rename = Some(rename);
+                        }
+                    }
+                    "default_with" => {
+                        let lit_str = parse_str_value(&meta)?;
+                        match lit_str.parse() {
+                            Ok(default_with) => options.default_with = Some((default_with, lit_str)),
+                            Err(error) => errors.push(error),
+                        }
+                    }
+                    _ => {
+                        errors.push(unknown_key(&meta, "builder", FIELD_KEYS));
+                        skip_value(&meta)?;
+                    }
+                }
+                Ok(())
+            });
+
+            // Malformed syntax stops the parsing of the current attribute, but not of the others
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        options
+    }
+}
+
+// Parses `each(name = "...", into)`, returning `None` if no valid name was given
+fn parse_each(meta: &ParseNestedMeta, errors: &mut Errors) -> syn::Result<Option<EachSetter>> {
+    let mut name = None;
+    let mut into = false;
+    let mut name_given = false;
+
+    meta.parse_nested_meta(|each_meta| {
+        match key_name(&each_meta).as_str() {
+            "name" => {
+                name_given = true;
+                name = parse_ident_value(&each_meta, errors)?;
+            }
+            "into" => into = true,
+            _ => {
+                errors.push(unknown_key(&each_meta, "each(...)", EACH_KEYS));
+                skip_value(&each_meta)?;
+            }
+        }
+        Ok(())
+    })?;
+
+    if !name_given {
+        errors.push(meta.error("expected `each(name = \"...\")`"));
+    }
+
+    Ok(name.map(|name| EachSetter { name, into }))
+}
+
+// Options given to the whole builder through `#[builder(...)]` on the struct or enum
+#[derive(Default)]
+pub(crate) struct ContainerOptions {
+    // Naming convention from `#[builder(rename_all = "...")]` applied to the entire field setters
+    pub(crate) rename_all: Option<(RenameRule, LitStr)>,
+    // Prefix from `#[builder(prefix = "...")]` added to the entire field setters, such as `with_`
+    pub(crate) prefix: Option<LitStr>,
+}
+
+impl ContainerOptions {
+    pub(crate) fn parse(attrs: &[Attribute], errors: &mut Errors) -> Self {
+        let mut options = ContainerOptions::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+            let result = attr.parse_nested_meta(|meta| {
+                match key_name(&meta).as_str() {
+                    "rename_all" => {
+                        let lit_str = parse_str_value(&meta)?;
+                        match RenameRule::from_str(&lit_str.value()) {
+                            Some(rule) => options.rename_all = Some((rule, lit_str)),
+                            None => errors.push(syn::Error::new(
+                                lit_str.span(),
+                                format!(
+                                    "unknown `rename_all` convention, expected one of {}",
+                                    RenameRule::NAMES.join(", "),
+                                ),
+                            )),
+                        }
+                    }
+                    "prefix" => options.prefix = Some(parse_str_value(&meta)?),
+                    _ => {
+                        errors.push(unknown_key(&meta, "builder", CONTAINER_KEYS));
+                        skip_value(&meta)?;
+                    }
+                }
+                Ok(())
+            });
+
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        options
+    }
+}
+
+// The key of a nested meta as written by the caller, such as `each` or `std::each`
+fn key_name(meta: &ParseNestedMeta) -> String {
+    let path = &meta.path;
+    quote::quote!(#path).to_string().replace(' ', "")
+}
+
+// Parses the `= "..."` following a key. Any expression is accepted syntactically, such that
+// the parsing of the following keys can continue when it is not a string literal.
+fn parse_str_value(meta: &ParseNestedMeta) -> syn::Result<LitStr> {
+    let expr: Expr = meta.value()?.parse()?;
+
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(lit_str), .. }) => Ok(lit_str),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            format!("expected `{} = \"...\"`", key_name(meta)),
+        )),
+    }
+}
+
+// Parses the `= "..."` following a key into an identifier. Returns `None` when the string is
+// not a valid identifier, after pushing the error, as the value has still been consumed.
+fn parse_ident_value(meta: &ParseNestedMeta, errors: &mut Errors) -> syn::Result<Option<Ident>> {
+    let lit_str = parse_str_value(meta)?;
+    match lit_str.parse() {
+        Ok(ident) => Ok(Some(ident)),
+        Err(_) => {
+            errors.push(syn::Error::new(
+                lit_str.span(),
+                format!("`{}` is not a valid identifier", lit_str.value()),
+            ));
+            Ok(None)
+        }
+    }
+}
+
+// Consumes whatever follows an unknown key, either `= value` or `(...)`, such that parsing
+// can carry on with the next key
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        let _: Expr = meta.value()?.parse()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let _content;
+        syn::parenthesized!(_content in meta.input);
+    }
+    Ok(())
+}
+
+// Error for a key that is not in `expected`, suggesting the closest valid key if there is one
+fn unknown_key(meta: &ParseNestedMeta, context: &str, expected: &[&str]) -> syn::Error {
+    let key = key_name(meta);
+
+    let message = match closest_key(&key, expected) {
+        Some(suggestion) => format!(
+            "unknown key `{}` in `{}`, did you mean `{}`?",
+            key, context, suggestion,
+        ),
+        None => format!(
+            "unknown key `{}` in `{}`, expected one of {}",
+            key,
+            context,
+            expected
+                .iter()
+                .map(|key| format!("`{}`", key))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    };
+
+    meta.error(message)
+}
+
+// Returns the key in `expected` that is the fewest edits away from `key`, as long as the two are
+// close enough for `key` to plausibly be a typo of it
+fn closest_key<'a>(key: &str, expected: &[&'a str]) -> Option<&'a str> {
+    let max_distance = std::cmp::max(1, key.chars().count() / 3);
+
+    expected
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Distances from the prefix of `a` processed so far to every prefix of `b`
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_ch) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, b_ch) in b.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_ch != *b_ch);
+            let insertion = row[j] + 1;
+            let deletion = prev_row[j + 1] + 1;
+            row.push(substitution.min(insertion).min(deletion));
+        }
+        prev_row = row;
+    }
+
+    prev_row[b.len()]
+}
+
+// Naming conventions accepted by `#[builder(rename_all = "...")]`, following serde's names
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Snake,
+    ScreamingSnake,
+    Camel,
+    Pascal,
+}
+
+impl RenameRule {
+    const NAMES: [&'static str; 6] = [
+        "\"lowercase\"",
+        "\"UPPERCASE\"",
+        "\"snake_case\"",
+        "\"SCREAMING_SNAKE_CASE\"",
+        "\"camelCase\"",
+        "\"PascalCase\"",
+    ];
+
+    fn from_str(name: &str) -> Option<Self> {
+        match name {
+            "lowercase" => Some(RenameRule::Lower),
+            "UPPERCASE" => Some(RenameRule::Upper),
+            "snake_case" => Some(RenameRule::Snake),
+            "SCREAMING_SNAKE_CASE" => Some(RenameRule::ScreamingSnake),
+            "camelCase" => Some(RenameRule::Camel),
+            "PascalCase" => Some(RenameRule::Pascal),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+
+        match self {
+            RenameRule::Lower => words.concat(),
+            RenameRule::Upper => words.concat().to_uppercase(),
+            RenameRule::Snake => words.join("_"),
+            RenameRule::ScreamingSnake => words.join("_").to_uppercase(),
+            RenameRule::Camel => {
+                let mut camel_case = words.first().cloned().unwrap_or_default();
+                camel_case.extend(words.iter().skip(1).map(|word| capitalize(word)));
+                camel_case
+            }
+            RenameRule::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+}
+
+// Splits a `snake_case`, `camelCase` or `PascalCase` name into its lowercase words
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut prev_lowercase = false;
+
+    for ch in name.chars() {
+        if ch == '_' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else if ch.is_uppercase() && prev_lowercase {
+            words.push(std::mem::take(&mut word));
+        }
+
+        if ch != '_' {
+            word.extend(ch.to_lowercase());
+        }
+        prev_lowercase = ch.is_lowercase() || ch.is_ascii_digit();
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,256 +1,538 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
-use syn::{Expr, Lit, Type, Data, Fields, PathArguments, GenericArgument, Ident};
+use syn::visit::{self, Visit};
+use syn::{Data, DeriveInput, Expr, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Type};
 use std::collections::HashMap;
-use syn::spanned::Spanned;
+
+mod attr;
+
+use attr::{ContainerOptions, Errors, FieldOptions, RenameRule};
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive_builder(input: TokenStream) -> TokenStream {
     let syn_tree: syn::DeriveInput = syn::parse_macro_input!(input);
 
-    let ident = syn_tree.ident;
+    expand(&syn_tree)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let fields_named = match syn_tree.data {
-        Data::Struct(data_struct) => {
-            match data_struct.fields {
-                Fields::Named(fields_named) => fields_named,
-                _ => unimplemented!(),
-            }
-        }
-        _ => unimplemented!(),
-    };
+// The way a field is stored in the builder and how it is turned back into the target field
+enum FieldKind<'a> {
+    // A field that must be set before calling `build`
+    Required,
+    // An `Option<T>` field, holding the inner `T`
+    Optional(&'a Type),
+    // A `Vec<T>` field, holding the element type `T`
+    Repeated(&'a Type),
+}
 
-    let mut optional_fields = HashMap::<&Ident, &Type>::new();
-    let mut vec_fields = HashMap::<&Ident, &Type>::new();
-    let mut fields = HashMap::<&Ident, &Type>::new();
+struct BuilderField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    kind: FieldKind<'a>,
+    // Options from the field's `#[builder(...)]` attributes
+    options: FieldOptions,
+}
 
-    // Goes through each field and checks for the Option field
-    for field in fields_named.named.iter() {
-        let field_ident = if let Some(field_ident) = &field.ident {
+impl<'a> BuilderField<'a> {
+    // Collects the field along with its options, pushing any problem with them into `errors`
+    fn new(field: &'a Field, errors: &mut Errors) -> Self {
+        let ident = if let Some(field_ident) = &field.ident {
             field_ident
         } else {
             unimplemented!()
         };
 
-        if let Type::Path(type_path) = &field.ty {
-            // Since we are only looking for the `Option` type, we only check the last argument
-            if let Some(path_seg) = type_path.path.segments.last() {
-                match path_seg.ident.to_string().as_ref() {
-                    "Option" => {
-                        if let PathArguments::AngleBracketed(angle_bracketed_ga) = &path_seg.arguments {
-                            // We know `Option` takes a single generic argument, so we only take the
-                            // first one out of the sequence
-                            if let Some(GenericArgument::Type(generic_arg_ty)) =
-                                angle_bracketed_ga.args.first()
-                            {
-                                optional_fields.insert(field_ident, generic_arg_ty);
-                            }
-                        }
-                    }
-                    "Vec" => {
-                        if let PathArguments::AngleBracketed(angle_bracketed_ga) = &path_seg.arguments {
-                            // We know `Option` takes a single generic argument, so we only take the
-                            // first one out of the sequence
-                            if let Some(GenericArgument::Type(generic_arg_ty)) =
-                                angle_bracketed_ga.args.first()
-                            {
-                                vec_fields.insert(field_ident, generic_arg_ty);
-                            }
-                        }
-                    }
-                    _ => { fields.insert(field_ident, &field.ty); }
-                }
-            } else {
-                fields.insert(field_ident, &field.ty);
-            }
+        let kind = if let Some(inner_ty) = generic_inner_type(&field.ty, "Option") {
+            FieldKind::Optional(inner_ty)
+        } else if let Some(inner_ty) = generic_inner_type(&field.ty, "Vec") {
+            FieldKind::Repeated(inner_ty)
         } else {
-            fields.insert(field_ident, &field.ty);
+            FieldKind::Required
+        };
+
+        let options = FieldOptions::parse(
+            &field.attrs,
+            matches!(kind, FieldKind::Repeated(_)),
+            errors,
+        );
+
+        // Without a setter, a required field could only ever be filled in by its default
+        if options.skip_setter && options.default_with.is_none() && matches!(kind, FieldKind::Required) {
+            errors.push(syn::Error::new(
+                ident.span(),
+                "required field with `setter(skip)` needs a `default_with`",
+            ));
+        }
+
+        BuilderField {
+            ident,
+            ty: &field.ty,
+            kind,
+            options,
         }
     }
 
-    let mut builder_fields = TokenStream2::new();
-    let mut fields_instance = TokenStream2::new();
+    // Name of the setter for the entire field. An explicit `rename` is used as is, otherwise the
+    // container's `rename_all` and `prefix` are applied to the field's name.
+    fn setter_ident(&self, options: &ContainerOptions) -> syn::Result<Ident> {
+        if let Some(rename) = &self.options.rename {
+            return Ok(rename.clone());
+        }
 
-    for (ident, ty) in optional_fields.iter().chain(fields.iter()) {
-        let (builder_field, builder_field_instance) =
-        (
-            quote! { #ident: core::option::Option<#ty>, },
-            quote! { #ident: core::option::Option::None, },
-        );
-        builder_fields.extend(builder_field);
-        fields_instance.extend(builder_field_instance);
-    }
+        if options.rename_all.is_none() && options.prefix.is_none() {
+            return Ok(self.ident.clone());
+        }
 
-    for (ident, ty) in vec_fields.iter() {
-        let (builder_field, builder_field_instance) =
-        (
-            quote! { #ident: core::option::Option<std::vec::Vec<#ty>>, },
-            quote! { #ident: core::option::Option::None, },
-        );
-        builder_fields.extend(builder_field);
-        fields_instance.extend(builder_field_instance);
+        let field_name = self.ident.to_string();
+        // Raw identifiers such as `r#type` are renamed based on their actual name
+        let field_name = field_name.trim_start_matches("r#");
+
+        let renamed = match &options.rename_all {
+            Some((rule, _)) => rule.apply(field_name),
+            None => field_name.to_string(),
+        };
+        let setter_name = match &options.prefix {
+            Some(prefix) => format!("{}{}", prefix.value(), renamed),
+            None => renamed,
+        };
+
+        // Report names that are not valid identifiers, such as keywords, on the container
+        // attribute which produced them
+        let span = options
+            .rename_all
+            .as_ref()
+            .map(|(_, lit_str)| lit_str.span())
+            .or_else(|| options.prefix.as_ref().map(LitStr::span))
+            .unwrap_or_else(|| self.ident.span());
+
+        syn::parse_str::<Ident>(&setter_name)
+            .map(|ident| Ident::new(&ident.to_string(), self.ident.span()))
+            .map_err(|_| {
+                syn::Error::new(
+                    span,
+                    format!("setter name `{}` for field `{}` is not a valid identifier", setter_name, self.ident),
+                )
+            })
     }
 
-    // Setters that have the exact same name as the field and sets the entire field
-    let mut builder_setters = TokenStream2::new();
+    // The type held inside the builder's `Option`, which is also what the setter accepts and
+    // what the `default_with` closure has to return
+    fn storage_ty(&self) -> TokenStream2 {
+        match self.kind {
+            FieldKind::Required => {
+                let ty = self.ty;
+                quote! { #ty }
+            }
+            FieldKind::Optional(inner_ty) => quote! { #inner_ty },
+            FieldKind::Repeated(inner_ty) => quote! { std::vec::Vec<#inner_ty> },
+        }
+    }
+}
 
-    let mut vec_fields_with_attr = HashMap::<&Ident, TokenStream2>::new();
+// Returns `T` if `ty` is written as `<wrapper><T>`, for example `Option<T>` or `Vec<T>`
+fn generic_inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    if let Type::Path(type_path) = ty {
+        // We only check the last segment, such that `std::vec::Vec<T>` is also accepted
+        let path_seg = type_path.path.segments.last()?;
+        if path_seg.ident != wrapper {
+            return None;
+        }
+        if let PathArguments::AngleBracketed(angle_bracketed_ga) = &path_seg.arguments {
+            // Both `Option` and `Vec` take a single generic argument, so we only take the
+            // first one out of the sequence
+            if let Some(GenericArgument::Type(generic_arg_ty)) = angle_bracketed_ga.args.first() {
+                return Some(generic_arg_ty);
+            }
+        }
+    }
+    None
+}
 
-    for field in fields_named.named.iter() {
-        let field_ident = if let Some(field_ident) = &field.ident {
-            field_ident
-        } else {
-            unimplemented!()
-        };
+// Collects the builder fields read by a `default_with` closure through its parameter, for
+// example `b.connect_timeout` in `|b: &FooBuilder| b.connect_timeout.unwrap() * 2`
+struct FieldAccesses<'a> {
+    param: &'a Ident,
+    fields: Vec<Ident>,
+}
 
-        for attr in field.attrs.iter() {
-            let attr_expr: Expr = attr.parse_args().unwrap();
-            let builder_fn_stream = if let Expr::Assign(ref each_expr) = attr_expr {
-                if let Expr::Path(ref expr_path) = *each_expr.left {
-                    if let Some(path_seg) = expr_path.path.segments.first() {
-                        if path_seg.ident == "each" {
-                            if let Expr::Lit(ref expr) = each_expr.right.as_ref() {
-                                if let Lit::Str(lit_str) = &expr.lit {
-                                    let token: Ident = lit_str.parse().unwrap();
-                                    if let Some(ty) = vec_fields.get(field_ident) {
-                                        let mut token_stream = quote! {
-                                            pub fn #token(&mut self, #token: #ty) -> &mut Self {
-                                                if let core::option::Option::Some(inner_value) =
-                                                    self.#field_ident.as_mut()
-                                                {
-                                                    inner_value.push(#token)
-                                                } else {
-                                                    self.#field_ident =
-                                                        core::option::Option::Some(std::vec![#token]);
-                                                }
-                                                self
-                                            }
-                                        };
-
-                                        if token.to_string() != field_ident.to_string() {
-                                            token_stream.extend(
-                                                quote! {
-                                                    pub fn #field_ident(
-                                                        &mut self,
-                                                        #field_ident: std::vec::Vec<#ty>,
-                                                    ) -> &mut Self {
-                                                        self.#field_ident =
-                                                            core::option::Option::Some(#field_ident);
-                                                        self
-                                                    }
-                                                }
-                                            );
-                                        }
-                                        token_stream
-                                    } else {
-                                        Err(syn::Error::new(
-                                            each_expr.span(),
-                                            "expected `builder(each = \"...\")`",
-                                        )).unwrap_or_else(syn::Error::into_compile_error).into()
-                                    }
-                                } else {
-                                    Err(syn::Error::new(
-                                        each_expr.span(),
-                                        "expected `builder(each = \"...\")`",
-                                    )).unwrap_or_else(syn::Error::into_compile_error).into()
-                                }
-                            } else {
-                                Err(syn::Error::new(
-                                    each_expr.span(),
-                                    "expected `builder(each = \"...\")`",
-                                )).unwrap_or_else(syn::Error::into_compile_error).into()
-                            }
-                        } else {
-                            Err(syn::Error::new(
-                                each_expr.span(),
-                                "expected `builder(each = \"...\")`",
-                            )).unwrap_or_else(syn::Error::into_compile_error).into()
-                        }
-                    } else {
-                        Err(syn::Error::new(
-                            each_expr.span(),
-                            "expected `builder(each = \"...\")`",
-                        )).unwrap_or_else(syn::Error::into_compile_error).into()
+impl<'ast> Visit<'ast> for FieldAccesses<'_> {
+    fn visit_expr_field(&mut self, expr_field: &'ast syn::ExprField) {
+        if let Expr::Path(expr_path) = expr_field.base.as_ref() {
+            if expr_path.path.is_ident(self.param) {
+                if let syn::Member::Named(field_ident) = &expr_field.member {
+                    if !self.fields.contains(field_ident) {
+                        self.fields.push(field_ident.clone());
                     }
-                } else {
-                    Err(syn::Error::new(
-                        each_expr.span(),
-                        "expected `builder(each = \"...\")`",
-                    )).unwrap_or_else(syn::Error::into_compile_error).into()
                 }
-            } else {
-                Err(syn::Error::new(
-                    attr_expr.span(),
-                    "expected `builder(each = \"...\")`",
-                )).unwrap_or_else(syn::Error::into_compile_error).into()
-            };
-            vec_fields_with_attr.insert(field_ident, builder_fn_stream);
+            }
+        }
+        visit::visit_expr_field(self, expr_field);
+    }
+}
+
+// Returns the fields a `default_with` expression depends on. Only closures can be inspected,
+// any other expression (such as a path to a function) is assumed to not depend on anything.
+fn default_dependencies(default_with: &Expr) -> Vec<Ident> {
+    let closure = if let Expr::Closure(closure) = default_with {
+        closure
+    } else {
+        return vec![];
+    };
+
+    let param = match closure.inputs.first() {
+        Some(syn::Pat::Ident(pat_ident)) => &pat_ident.ident,
+        Some(syn::Pat::Type(pat_type)) => match pat_type.pat.as_ref() {
+            syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+            _ => return vec![],
+        },
+        _ => return vec![],
+    };
+
+    let mut accesses = FieldAccesses { param, fields: vec![] };
+    accesses.visit_expr(&closure.body);
+    accesses.fields
+}
+
+// Orders the fields that have a `default_with` such that every default is computed after the
+// defaults it reads from. Returns an error if the defaults depend on each other in a cycle.
+fn default_order<'a, 'b>(fields: &'b [BuilderField<'a>]) -> syn::Result<Vec<&'b BuilderField<'a>>> {
+    // Dependencies of each defaulted field on other defaulted fields
+    let mut dependencies = HashMap::<&Ident, Vec<usize>>::new();
+
+    for field in fields.iter() {
+        if let Some((default_with, _)) = &field.options.default_with {
+            let field_deps = default_dependencies(default_with)
+                .iter()
+                .filter_map(|dep| {
+                    fields
+                        .iter()
+                        .position(|other| other.ident == dep && other.options.default_with.is_some())
+                })
+                .collect();
+            dependencies.insert(field.ident, field_deps);
         }
     }
 
-    for (ident, ty) in fields.iter().chain(optional_fields.iter()) {
-        // Check if the ident of the field is the same as the literal in the attribute
-        let builder_setter = quote! {
-            fn #ident(&mut self, #ident: #ty) -> &mut Self {
-                self.#ident = core::option::Option::Some(#ident);
-                self
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a, 'b>(
+        idx: usize,
+        fields: &'b [BuilderField<'a>],
+        dependencies: &HashMap<&Ident, Vec<usize>>,
+        marks: &mut HashMap<usize, Mark>,
+        stack: &mut Vec<usize>,
+        order: &mut Vec<&'b BuilderField<'a>>,
+    ) -> syn::Result<()> {
+        match marks.get(&idx) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                // The cycle starts where `idx` was first entered on the stack
+                let start = stack.iter().position(|&other| other == idx).unwrap_or(0);
+                let cycle = stack[start..]
+                    .iter()
+                    .chain(std::iter::once(&idx))
+                    .map(|&other| format!("`{}`", fields[other].ident))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let lit_str = &fields[idx].options.default_with.as_ref().unwrap().1;
+                return Err(syn::Error::new(
+                    lit_str.span(),
+                    format!("cycle between `default_with` fields: {}", cycle),
+                ));
             }
-        };
+            None => {}
+        }
+
+        marks.insert(idx, Mark::Visiting);
+        stack.push(idx);
+        for &dep in dependencies[fields[idx].ident].iter() {
+            visit(dep, fields, dependencies, marks, stack, order)?;
+        }
+        stack.pop();
+        marks.insert(idx, Mark::Done);
+        order.push(&fields[idx]);
 
-        builder_setters.extend(builder_setter);
+        Ok(())
     }
 
-    for (ident, ty) in vec_fields.iter() {
-        let vec_setter = if let Some(value) = vec_fields_with_attr.remove(ident) {
-            value
-        } else {
-            quote! {
-                fn #ident(&mut self, #ident: Vec<#ty>) -> &mut Self {
-                    self.#ident = core::option::Option::Some(#ident);
-                    self
+    let mut marks = HashMap::new();
+    let mut order = vec![];
+
+    for (idx, field) in fields.iter().enumerate() {
+        if field.options.default_with.is_some() {
+            visit(idx, fields, &dependencies, &mut marks, &mut vec![], &mut order)?;
+        }
+    }
+
+    Ok(order)
+}
+
+// A builder to generate, either for the struct itself or for one of the enum's variants
+struct BuilderTarget<'a> {
+    // Path instantiated by `build`, such as `Command` or `Message::Request`
+    constructor: TokenStream2,
+    // Function creating the builder, such as `builder` or `request_builder`
+    builder_fn: Ident,
+    builder_ident: Ident,
+    fields: Vec<BuilderField<'a>>,
+}
+
+fn expand(syn_tree: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &syn_tree.ident;
+
+    // All the attributes are parsed before anything is generated, such that every malformed
+    // `#[builder(...)]` across the container and its fields is reported together
+    let mut errors = Errors::default();
+    let options = ContainerOptions::parse(&syn_tree.attrs, &mut errors);
+
+    let targets = match &syn_tree.data {
+        Data::Struct(data_struct) => {
+            // Without field names there is nothing to name the setters after
+            let fields_named = match &data_struct.fields {
+                Fields::Named(fields_named) => fields_named,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "`Builder` can only be derived for structs with named fields and enums",
+                    ))
                 }
-            }
-        };
+            };
+
+            vec![BuilderTarget {
+                constructor: quote! { #ident },
+                builder_fn: format_ident!("builder"),
+                builder_ident: format_ident!("{}Builder", ident),
+                fields: fields_named
+                    .named
+                    .iter()
+                    .map(|field| BuilderField::new(field, &mut errors))
+                    .collect(),
+            }]
+        }
+        Data::Enum(data_enum) => {
+            // Each struct-like variant gets its own builder, for example `Message::Request { .. }`
+            // is built through `Message::request_builder()` into a `MessageRequestBuilder`.
+            // Tuple and unit variants are left alone, since there is nothing to name the setters
+            // after.
+            data_enum
+                .variants
+                .iter()
+                .filter_map(|variant| match &variant.fields {
+                    Fields::Named(fields_named) => Some((&variant.ident, fields_named)),
+                    _ => None,
+                })
+                .map(|(variant_ident, fields_named)| {
+                    let snake_case = RenameRule::Snake.apply(&variant_ident.to_string());
+
+                    BuilderTarget {
+                        constructor: quote! { #ident::#variant_ident },
+                        builder_fn: format_ident!("{}_builder", snake_case),
+                        builder_ident: format_ident!("{}{}Builder", ident, variant_ident),
+                        fields: fields_named
+                            .named
+                            .iter()
+                            .map(|field| BuilderField::new(field, &mut errors))
+                            .collect(),
+                    }
+                })
+                .collect()
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(ident, "`Builder` cannot be derived for unions"))
+        }
+    };
 
-        builder_setters.extend(vec_setter);
+    errors.finish()?;
+
+    let mut tokens = TokenStream2::new();
+
+    for target in targets.iter() {
+        tokens.extend(expand_builder(ident, target, &options)?);
     }
 
-    let mut original_fields = TokenStream2::new();
+    Ok(tokens)
+}
 
-    for (ident, _ty) in fields.iter() {
-        let field = quote! {
-            #ident: self.#ident.take().ok_or("Field is None".to_string())?,
-        };
+// Generates the builder for one of the targets of `ident`, along with the `ident::builder_fn()`
+// constructor. The `build` method instantiates `constructor { .. }`, which is the struct itself
+// or one of the enum's variants.
+fn expand_builder(
+    ident: &Ident,
+    target: &BuilderTarget<'_>,
+    options: &ContainerOptions,
+) -> syn::Result<TokenStream2> {
+    let BuilderTarget { constructor, builder_fn, builder_ident, fields } = target;
+
+    let mut builder_fields = TokenStream2::new();
+    let mut fields_instance = TokenStream2::new();
 
-        original_fields.extend(field);
+    for field in fields.iter() {
+        let ident = field.ident;
+        let storage_ty = field.storage_ty();
+
+        builder_fields.extend(quote! { #ident: core::option::Option<#storage_ty>, });
+        fields_instance.extend(quote! { #ident: core::option::Option::None, });
     }
 
-    let mut original_optional_fields = TokenStream2::new();
+    let mut builder_setters = TokenStream2::new();
 
-    for (ident, _ty) in optional_fields.iter() {
-        let field = quote! {
-            #ident: self.#ident.take(),
-        };
+    // Every method of the builder, used to report setters whose names collide. `build` is
+    // generated for every builder, so it is taken from the start.
+    let mut method_names = HashMap::<String, Option<String>>::new();
+    method_names.insert("build".to_string(), None);
+
+    let mut claim_name = |method_ident: &Ident, field: &BuilderField<'_>| -> syn::Result<()> {
+        match method_names.insert(method_ident.to_string(), Some(field.ident.to_string())) {
+            Some(Some(other_field)) => Err(syn::Error::new(
+                method_ident.span(),
+                format!(
+                    "setter `{}` for field `{}` collides with a setter for field `{}`",
+                    method_ident, field.ident, other_field,
+                ),
+            )),
+            Some(None) => Err(syn::Error::new(
+                method_ident.span(),
+                format!(
+                    "setter `{}` for field `{}` collides with the builder's `{}` method",
+                    method_ident, field.ident, method_ident,
+                ),
+            )),
+            None => Ok(()),
+        }
+    };
+
+    for field in fields.iter() {
+        let ident = field.ident;
+        let storage_ty = field.storage_ty();
+        let setter_ident = field.setter_ident(options)?;
+
+        if let (Some(each), FieldKind::Repeated(ty)) = (&field.options.each, &field.kind) {
+            let each_ident = &each.name;
+            let (arg_ty, arg_value) = if each.into {
+                (quote! { impl core::convert::Into<#ty> }, quote! { #each_ident.into() })
+            } else {
+                (quote! { #ty }, quote! { #each_ident })
+            };
+
+            claim_name(each_ident, field)?;
+            builder_setters.extend(quote! {
+                pub fn #each_ident(&mut self, #each_ident: #arg_ty) -> &mut Self {
+                    self.#ident
+                        .get_or_insert_with(std::vec::Vec::new)
+                        .push(#arg_value);
+                    self
+                }
+            });
+        }
+
+        if let (true, FieldKind::Repeated(ty)) = (field.options.extend, &field.kind) {
+            let extend_ident = format_ident!("{}_extend", setter_ident);
+
+            claim_name(&extend_ident, field)?;
+            builder_setters.extend(quote! {
+                pub fn #extend_ident<I>(&mut self, #ident: I) -> &mut Self
+                where
+                    I: core::iter::IntoIterator<Item = #ty>,
+                {
+                    self.#ident
+                        .get_or_insert_with(std::vec::Vec::new)
+                        .extend(#ident);
+                    self
+                }
+            });
+        }
 
-        original_optional_fields.extend(field);
+        // If the one-at-a-time setter has the same name as the entire field setter, we avoid
+        // generating the latter, since the names would conflict
+        let each_collides = field.options.each.as_ref().is_some_and(|each| each.name == setter_ident);
+
+        if field.options.skip_setter || each_collides {
+            continue;
+        }
+
+        // Setter that sets the entire field, named after the field unless renamed
+        claim_name(&setter_ident, field)?;
+        builder_setters.extend(quote! {
+            pub fn #setter_ident(&mut self, #ident: #storage_ty) -> &mut Self {
+                self.#ident = core::option::Option::Some(#ident);
+                self
+            }
+        });
     }
 
-    let mut vec_original_fields = TokenStream2::new();
+    // Defaults are resolved before any field is taken out of the builder, such that they can
+    // read both the explicitly set fields and the defaults computed before them
+    let mut resolved_defaults = TokenStream2::new();
+
+    for field in default_order(fields)? {
+        let ident = field.ident;
+        let storage_ty = field.storage_ty();
+        let (default_with, _) = field.options.default_with.as_ref().unwrap();
+
+        // A required field read by the default may not have been set, which is reported the
+        // same way as when building without it rather than left to the default to unwrap
+        let required_checks = default_dependencies(default_with)
+            .into_iter()
+            .filter(|dep| {
+                fields.iter().any(|other| {
+                    other.ident == dep
+                        && matches!(other.kind, FieldKind::Required)
+                        && other.options.default_with.is_none()
+                })
+            })
+            .map(|dep| quote! { self.#dep.as_ref().ok_or("Field is None".to_string())?; });
+
+        resolved_defaults.extend(quote! {
+            if self.#ident.is_none() {
+                #(#required_checks)*
+                let default_value: #storage_ty = call_default_with(#default_with, &*self);
+                self.#ident = core::option::Option::Some(default_value);
+            }
+        });
+    }
 
-    for (ident, _ty) in vec_fields.iter() {
-        let field = quote! {
-            #ident: self.#ident.take().unwrap_or(vec![]),
-        };
+    // Passing the closure to a function that bounds it lets the compiler infer the type of its
+    // parameter, such that `|b| ...` does not need to spell out the builder type
+    if !resolved_defaults.is_empty() {
+        resolved_defaults = quote! {
+            fn call_default_with<T, F>(default_with: F, builder: &#builder_ident) -> T
+            where
+                F: core::ops::FnOnce(&#builder_ident) -> T,
+            {
+                default_with(builder)
+            }
 
-        vec_original_fields.extend(field);
+            #resolved_defaults
+        };
     }
 
-    let builder_ident = format_ident!("{}Builder", ident);
+    let mut original_fields = TokenStream2::new();
+
+    for field in fields.iter() {
+        let ident = field.ident;
+
+        original_fields.extend(match field.kind {
+            FieldKind::Required => quote! {
+                #ident: self.#ident.take().ok_or("Field is None".to_string())?,
+            },
+            FieldKind::Optional(_) => quote! {
+                #ident: self.#ident.take(),
+            },
+            FieldKind::Repeated(_) => quote! {
+                #ident: self.#ident.take().unwrap_or_default(),
+            },
+        });
+    }
 
-    let tokens = quote! {
+    Ok(quote! {
         impl #ident {
-            pub fn builder() -> #builder_ident {
+            pub fn #builder_fn() -> #builder_ident {
                 #builder_ident {
                     #fields_instance
                 }
@@ -258,24 +540,24 @@ pub fn derive_builder(input: TokenStream) -> TokenStream {
         }
 
         #[derive(Debug, Default)]
+        #[allow(non_snake_case)]
         pub struct #builder_ident {
             #builder_fields
         }
 
+        #[allow(non_snake_case)]
         impl #builder_ident {
             #builder_setters
 
             pub fn build(
                 &mut self
             ) -> core::result::Result<#ident, std::boxed::Box<dyn std::error::Error>> {
-                core::result::Result::Ok( #ident {
+                #resolved_defaults
+
+                core::result::Result::Ok( #constructor {
                     #original_fields
-                    #original_optional_fields
-                    #vec_original_fields
                 })
             }
         }
-    }.into();
-
-    tokens
+    })
 }
@@ -0,0 +1,59 @@
+// Some defaults can only be known once the other fields of the builder have
+// been set, for example a read timeout that defaults to twice the connect
+// timeout.
+//
+// Look for a field attribute #[builder(default_with = "...")] holding a closure
+// that receives the builder and returns the value the field's setter would
+// accept. The closure only runs inside `build` when the field was not set, and
+// only after the explicitly set fields are in place, so it can read them
+// through the builder. A default may also read another defaulted field, in
+// which case that default is computed first regardless of declaration order.
+//
+// The closure's parameter is known to be a reference to the builder, so it
+// does not need a type annotation. When a default reads a required field
+// that was never set, `build` returns the usual error instead of running it.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Connection {
+    id: String,
+    #[builder(default_with = "|b| b.id.clone().unwrap()")]
+    display_name: String,
+    #[builder(default_with = "|b: &ConnectionBuilder| b.connect_timeout.unwrap() * 2")]
+    read_timeout: u64,
+    #[builder(default_with = "|_| 10")]
+    connect_timeout: u64,
+    #[builder(default_with = "|b: &ConnectionBuilder| b.display_name.clone().unwrap()")]
+    log_prefix: Option<String>,
+    #[builder(each = "tag", default_with = "|_| vec![\"default\".to_owned()]")]
+    tags: Vec<String>,
+}
+
+fn main() {
+    assert!(Connection::builder().build().is_err());
+
+    let connection = Connection::builder()
+        .id("db-1".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(connection.display_name, "db-1");
+    assert_eq!(connection.connect_timeout, 10);
+    assert_eq!(connection.read_timeout, 20);
+    assert_eq!(connection.log_prefix.as_deref(), Some("db-1"));
+    assert_eq!(connection.tags, vec!["default"]);
+
+    let connection = Connection::builder()
+        .id("db-2".to_owned())
+        .display_name("primary".to_owned())
+        .connect_timeout(3)
+        .tag("replica".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(connection.display_name, "primary");
+    assert_eq!(connection.read_timeout, 6);
+    assert_eq!(connection.log_prefix.as_deref(), Some("primary"));
+    assert_eq!(connection.tags, vec!["replica"]);
+}
@@ -0,0 +1,17 @@
+// Defaults that read each other in a cycle can never be computed. Detect this
+// at expansion time, listing the fields involved and pointing at the
+// `default_with` literal of the field the cycle starts from.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Timeouts {
+    #[builder(default_with = "|b: &TimeoutsBuilder| b.write.unwrap() + 1")]
+    read: u64,
+    #[builder(default_with = "|b: &TimeoutsBuilder| b.idle.unwrap() + 1")]
+    write: u64,
+    #[builder(default_with = "|b: &TimeoutsBuilder| b.read.unwrap() + 1")]
+    idle: u64,
+}
+
+fn main() {}
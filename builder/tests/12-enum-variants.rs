@@ -0,0 +1,53 @@
+// Deriving Builder on an enum generates one builder per struct-like variant.
+// The builder for `Message::Request { .. }` is named `MessageRequestBuilder`
+// and is created through `Message::request_builder()`, while its `build`
+// method returns the enum itself.
+//
+// Fields of a variant follow the same rules as the fields of a struct, so
+// `Option` fields are optional, `Vec` fields accept `each` setters and
+// `default_with` closures receive the variant's builder. Tuple and unit
+// variants do not get a builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Message {
+    Request {
+        id: u64,
+        method: String,
+        #[builder(each = "param")]
+        params: Vec<String>,
+        #[builder(default_with = "|b: &MessageRequestBuilder| b.id.unwrap() * 10")]
+        timeout: u64,
+    },
+    KeepAlive {
+        peer: Option<String>,
+    },
+    Unit,
+    Tuple(u64),
+}
+
+fn main() {
+    let request = Message::request_builder()
+        .id(7)
+        .method("ping".to_owned())
+        .param("a".to_owned())
+        .param("b".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        request,
+        Message::Request {
+            id: 7,
+            method: "ping".to_owned(),
+            params: vec!["a".to_owned(), "b".to_owned()],
+            timeout: 70,
+        }
+    );
+
+    let keep_alive = Message::keep_alive_builder().build().unwrap();
+    assert_eq!(keep_alive, Message::KeepAlive { peer: None });
+
+    assert!(Message::request_builder().id(1).build().is_err());
+}
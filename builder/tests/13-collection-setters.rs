@@ -0,0 +1,35 @@
+// Collection fields get finer control over which setters are generated.
+//
+// The long form #[builder(each(name = "...", into))] names the one-at-a-time
+// setter and, with `into`, makes it accept anything convertible into the
+// element type. #[builder(setter(skip))] drops the setter for the entire
+// field, leaving only the one-at-a-time setter in the builder's API.
+//
+// #[builder(extend)] adds a `<field>_extend` method that appends every item of
+// an iterator to the field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each(name = "arg", into), setter(skip), extend)]
+    args: Vec<String>,
+    #[builder(each = "env", extend)]
+    env: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build")
+        .args_extend(vec!["--release".to_owned(), "--locked".to_owned()])
+        .arg(String::from("--verbose"))
+        .env_extend(["A=1".to_owned()])
+        .env("B=2".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build", "--release", "--locked", "--verbose"]);
+    assert_eq!(command.env, vec!["A=1", "B=2"]);
+}
@@ -0,0 +1,34 @@
+// The whole-collection setter is no longer generated when the field has
+// #[builder(setter(skip))], leaving only the one-at-a-time setter. The name
+// is then free, so the builder can get an `args` method of its own, which
+// would otherwise conflict with the generated one.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each(name = "arg"), setter(skip))]
+    args: Vec<String>,
+}
+
+impl CommandBuilder {
+    pub fn args(&mut self, line: &str) -> &mut Self {
+        for arg in line.split_whitespace() {
+            self.arg(arg.to_owned());
+        }
+        self
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args("build --release")
+        .arg("--locked".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release", "--locked"]);
+}
@@ -0,0 +1,48 @@
+// Setter names can be chosen independently of the field names.
+//
+// The container attribute #[builder(rename_all = "...")] converts every field
+// name to one of serde's naming conventions, such as "snake_case", before
+// using it as the name of the setter for the entire field, while
+// #[builder(prefix = "...")] puts a prefix in front of it. A field attribute
+// #[builder(rename = "...")] names that setter explicitly, ignoring both.
+//
+// The builder fields themselves, and the `each` setters, keep their names.
+
+use derive_builder::Builder;
+
+#[allow(non_snake_case)]
+#[derive(Builder)]
+#[builder(rename_all = "snake_case", prefix = "with_")]
+pub struct Request {
+    userId: u64,
+    requestTimeout: Option<u64>,
+    #[builder(each = "header", extend)]
+    extraHeaders: Vec<String>,
+    #[builder(rename = "body")]
+    payload: String,
+}
+
+fn main() {
+    let request = Request::builder()
+        .with_user_id(7)
+        .with_request_timeout(30)
+        .header("A: 1".to_owned())
+        .with_extra_headers_extend(vec!["B: 2".to_owned()])
+        .body("{}".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(request.userId, 7);
+    assert_eq!(request.requestTimeout, Some(30));
+    assert_eq!(request.extraHeaders, vec!["A: 1", "B: 2"]);
+    assert_eq!(request.payload, "{}");
+
+    let request = Request::builder()
+        .with_user_id(8)
+        .with_extra_headers(vec![])
+        .body("".to_owned())
+        .build()
+        .unwrap();
+
+    assert!(request.requestTimeout.is_none());
+}
@@ -0,0 +1,15 @@
+// Renaming makes it possible for two fields to end up with the same setter
+// name. Report the collision at expansion time rather than leaving the caller
+// with a duplicate definition error inside the generated code.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(rename = "timeout")]
+    read_timeout: u64,
+    #[builder(rename = "timeout")]
+    write_timeout: u64,
+}
+
+fn main() {}
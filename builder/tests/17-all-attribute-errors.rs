@@ -0,0 +1,23 @@
+// Rather than stopping at the first problem, report every malformed
+// #[builder(...)] key on the container and across all of the fields in one go,
+// so the caller can fix them all before compiling again.
+//
+// Keys that look like a typo of a valid key come with a suggestion, while any
+// other unknown key lists the keys that are accepted in its place.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(rename_al = "snake_case")]
+pub struct Command {
+    #[builder(defualt_with = "|_| String::new()")]
+    executable: String,
+    #[builder(each(nme = "arg"), setter(skp))]
+    args: Vec<String>,
+    #[builder(each = "env", colour = "red")]
+    env: Vec<String>,
+    #[builder(rename = 5)]
+    current_dir: Option<String>,
+}
+
+fn main() {}
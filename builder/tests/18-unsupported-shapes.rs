@@ -0,0 +1,19 @@
+// Setters are named after the fields they set, so tuple structs and unit
+// structs cannot get a builder, and neither can unions, which only ever hold
+// one of their fields. Each of them is reported on its name.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Pair(String, u16);
+
+#[derive(Builder)]
+pub struct Marker;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
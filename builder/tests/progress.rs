@@ -10,4 +10,13 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-default-with.rs");
+    t.compile_fail("tests/11-default-with-cycle.rs");
+    t.pass("tests/12-enum-variants.rs");
+    t.pass("tests/13-collection-setters.rs");
+    t.pass("tests/14-skipped-setter.rs");
+    t.pass("tests/15-rename-setters.rs");
+    t.compile_fail("tests/16-setter-collision.rs");
+    t.compile_fail("tests/17-all-attribute-errors.rs");
+    t.compile_fail("tests/18-unsupported-shapes.rs");
 }
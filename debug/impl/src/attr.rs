@@ -0,0 +1,573 @@
+// Parsing of the `#[debug ...]` attributes found on the container and on its fields
+
+use crate::format;
+use syn::punctuated::Punctuated;
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Attribute, LitInt, LitStr, Token, Type, WherePredicate};
+
+pub(crate) type Bounds = Punctuated<WherePredicate, Token![,]>;
+
+// Options given through `#[debug(...)]` on the struct or enum, or on one of the enum's variants
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    // Where predicates from `#[debug(bound = "...")]`, replacing all of the inferred bounds
+    pub(crate) bound: Option<Bounds>,
+    // Template from `#[debug(fmt = "...")]`, written in place of the `debug_struct` output
+    pub(crate) fmt: Option<LitStr>,
+    // Layout from `#[debug(style = "...")]`, given on an enum for all of its variants
+    pub(crate) style: Option<Style>,
+    // Set by `#[debug(visitor)]`, also implements `DebugFields` for structured logging
+    pub(crate) visitor: bool,
+    // Format strings from `#[debug(format_type(u8 = "...", ...))]`, keyed by type name
+    pub(crate) format_types: Vec<(syn::Path, LitStr)>,
+    // Computed entries from `#[debug(extra(name = "...", expr = "..."))]`, written after the fields
+    pub(crate) extras: Vec<Extra>,
+    // Set by `#[debug(transparent)]`, writes the only field as if it were the whole value
+    pub(crate) transparent: bool,
+}
+
+// An entry that is not a field but the value of an expression, which can refer to `self`
+#[derive(Clone)]
+pub(crate) struct Extra {
+    pub(crate) name: LitStr,
+    pub(crate) expr: syn::Expr,
+}
+
+// How the fields are laid out by the generated impl
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Style {
+    // `Name { a: 1, b: 2 }` through `debug_struct`
+    Struct,
+    // `Name(1, 2)` through `debug_tuple`
+    Tuple,
+    // `{"a": 1, "b": 2}` through `debug_map`
+    Map,
+    // `Name a=1 b=2` on a single line, even under `{:#?}`
+    Compact,
+}
+
+impl Style {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "struct" => Ok(Style::Struct),
+            "tuple" => Ok(Style::Tuple),
+            "map" => Ok(Style::Map),
+            "compact" => Ok(Style::Compact),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                "expected one of `struct`, `tuple`, `map` or `compact`",
+            )),
+        }
+    }
+}
+
+// The item a `ContainerAttrs` is parsed from, as some options only apply to some of them
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Target {
+    Struct,
+    Enum,
+    Variant,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute], target: Target) -> syn::Result<Self> {
+        let mut container_attrs = ContainerAttrs::default();
+        let mut seen_keys = SeenKeys::default();
+        let mut transparent = None;
+
+        for attr in debug_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                // Any number of computed entries can be added, as long as their names differ
+                if meta.path.is_ident("extra") && target != Target::Enum {
+                    let extra = Extra::parse(&meta)?;
+                    let name = extra.name.value();
+                    if container_attrs.extras.iter().any(|seen| seen.name.value() == name) {
+                        return Err(syn::Error::new_spanned(
+                            &extra.name,
+                            format!("the extra entry `{}` is given more than once", name),
+                        ));
+                    }
+                    container_attrs.extras.push(extra);
+                    return Ok(());
+                }
+
+                seen_keys.insert(&meta.path)?;
+
+                if meta.path.is_ident("bound") && target != Target::Variant {
+                    container_attrs.bound = Some(parse_bounds(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("fmt") && target == Target::Enum {
+                    Err(meta.error("`debug(fmt = \"...\")` is given on each variant of an enum"))
+                } else if meta.path.is_ident("fmt") {
+                    container_attrs.fmt = Some(lit_str(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("style") {
+                    let style = lit_str(&meta.value()?.parse()?)?;
+                    container_attrs.style = Some(Style::parse(&style)?);
+                    Ok(())
+                } else if meta.path.is_ident("transparent") && target != Target::Enum {
+                    container_attrs.transparent = true;
+                    transparent = Some(meta.path.span());
+                    Ok(())
+                } else if meta.path.is_ident("visitor") && target != Target::Variant {
+                    container_attrs.visitor = true;
+                    Ok(())
+                } else if meta.path.is_ident("format_type") && target != Target::Variant {
+                    let mut seen_types = SeenKeys::default();
+                    meta.parse_nested_meta(|format_type| {
+                        seen_types.insert(&format_type.path)?;
+                        let format = lit_str(&format_type.value()?.parse()?)?;
+                        format::validate(&format)?;
+                        container_attrs.format_types.push((format_type.path, format));
+                        Ok(())
+                    })
+                } else {
+                    Err(meta.error(match target {
+                        Target::Struct => {
+                            "expected `bound`, `fmt`, `style`, `visitor`, `format_type`, `extra` \
+                             or `transparent` inside `debug(...)`"
+                        }
+                        Target::Enum => {
+                            "expected `bound`, `style`, `visitor` or `format_type` inside \
+                             `debug(...)`"
+                        }
+                        Target::Variant => {
+                            "expected `fmt`, `style`, `extra` or `transparent` inside \
+                             `debug(...)`"
+                        }
+                    }))
+                }
+            })?;
+        }
+
+        // A template replaces the layout entirely, so a style next to it would never apply
+        if let (Some(fmt), Some(_)) = (&container_attrs.fmt, container_attrs.style) {
+            return Err(syn::Error::new_spanned(
+                fmt,
+                "`debug(fmt = \"...\")` cannot be combined with `debug(style = \"...\")`",
+            ));
+        }
+
+        // A template is written as is, leaving no place for the computed entries
+        if let (Some(fmt), Some(_)) = (&container_attrs.fmt, container_attrs.extras.first()) {
+            return Err(syn::Error::new_spanned(
+                fmt,
+                "`debug(fmt = \"...\")` cannot be combined with `debug(extra(...))`",
+            ));
+        }
+
+        // The field is written in place of the whole value, so there is nothing to lay out
+        if let Some(span) = transparent {
+            let other = if container_attrs.fmt.is_some() {
+                Some("`fmt`")
+            } else if container_attrs.style.is_some() {
+                Some("`style`")
+            } else if !container_attrs.extras.is_empty() {
+                Some("`extra`")
+            } else {
+                None
+            };
+            if let Some(other) = other {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`transparent` cannot be combined with {}", other),
+                ));
+            }
+        }
+
+        Ok(container_attrs)
+    }
+}
+
+impl Extra {
+    // Parses the `name = "..."` and `expr = "..."` pairs of `extra(...)`, which are both required
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut name = None;
+        let mut expr = None;
+        let mut seen_keys = SeenKeys::default();
+
+        meta.parse_nested_meta(|extra| {
+            seen_keys.insert(&extra.path)?;
+
+            if extra.path.is_ident("name") {
+                name = Some(lit_str(&extra.value()?.parse()?)?);
+                Ok(())
+            } else if extra.path.is_ident("expr") {
+                expr = Some(parse_lit_str(&extra.value()?.parse()?)?);
+                Ok(())
+            } else {
+                Err(extra.error("expected `name` or `expr` inside `debug(extra(...))`"))
+            }
+        })?;
+
+        match (name, expr) {
+            (Some(name), Some(expr)) => Ok(Extra { name, expr }),
+            _ => Err(meta.error("expected `extra(name = \"...\", expr = \"...\")`")),
+        }
+    }
+}
+
+// Options given through `#[display("...", bound = "...")]` on a `CustomDisplay` struct, enum or
+// variant
+#[derive(Default)]
+pub(crate) struct DisplayAttrs {
+    // The template, which is required everywhere but on the enum itself and its unit variants
+    pub(crate) template: Option<LitStr>,
+    // Where predicates replacing all of the inferred `Display` bounds, only given on the container
+    pub(crate) bound: Option<Bounds>,
+}
+
+impl DisplayAttrs {
+    pub(crate) fn parse(attrs: &[Attribute], target: Target) -> syn::Result<Self> {
+        let mut display_attrs = DisplayAttrs::default();
+        let expected = match target {
+            Target::Struct => "expected `display(\"...\")` or `display(\"...\", bound = \"...\")`",
+            Target::Enum => {
+                "expected `display(bound = \"...\")`, templates are given on each variant of an \
+                 enum"
+            }
+            Target::Variant => "expected `display(\"...\")`",
+        };
+
+        let mut display_attrs_iter = attrs.iter().filter(|attr| attr.path().is_ident("display"));
+        if let Some(attr) = display_attrs_iter.next() {
+            attr.parse_args_with(|input: ParseStream| {
+                if input.peek(LitStr) {
+                    let template: LitStr = input.parse()?;
+                    if target == Target::Enum {
+                        return Err(syn::Error::new_spanned(template, expected));
+                    }
+                    display_attrs.template = Some(template);
+                    if !input.is_empty() {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+
+                if !input.is_empty() {
+                    let key: syn::Ident = input.parse()?;
+                    if key != "bound" || target == Target::Variant {
+                        return Err(syn::Error::new_spanned(key, expected));
+                    }
+                    input.parse::<Token![=]>()?;
+                    display_attrs.bound = Some(parse_bounds(&input.parse()?)?);
+                    if !input.is_empty() {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+
+                if !input.is_empty() {
+                    return Err(input.error(expected));
+                }
+
+                Ok(())
+            })?;
+        }
+        if let Some(attr) = display_attrs_iter.next() {
+            return Err(syn::Error::new_spanned(attr, "`display` is given more than once"));
+        }
+
+        Ok(display_attrs)
+    }
+}
+
+// Options given to a single field through `#[debug = "..."]` or `#[debug(...)]`
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    // Format string from `#[debug = "..."]`, used in place of `{:?}`
+    pub(crate) format: Option<LitStr>,
+    // Format string from `#[debug(alt = "...")]`, used in place of `format` under `{:#?}`
+    pub(crate) alt: Option<LitStr>,
+    // Where predicates from `#[debug(bound = "...")]`, replacing the bounds inferred from the
+    // type of this field only
+    pub(crate) bound: Option<Bounds>,
+    // Set by `#[debug(skip)]`, leaves the field out of the output
+    pub(crate) skip: bool,
+    // Predicate from `#[debug(skip_if = "...")]`, called with a reference to the field to decide
+    // whether it is left out of the output
+    pub(crate) skip_if: Option<syn::Expr>,
+    // Set by `#[debug(redact)]` or `#[debug(redact = "len")]`, hides the value of the field
+    pub(crate) redact: Option<Redact>,
+    // Function from `#[debug(with = "...")]`, called with a reference to the field and the
+    // formatter in place of the field's `Debug` impl
+    pub(crate) with: Option<syn::ExprPath>,
+    // Name from `#[debug(rename = "...")]`, written in place of the field's own name
+    pub(crate) rename: Option<LitStr>,
+    // Set by `#[debug(flatten)]`, writes the fields of the field's value in place of the field
+    pub(crate) flatten: bool,
+    // Format string from `#[debug(each = "...")]`, used for every element of a collection
+    pub(crate) each: Option<LitStr>,
+    // Number of elements from `#[debug(max_items = N)]`, past which a collection is cut short
+    pub(crate) max_items: Option<usize>,
+    // Number of characters from `#[debug(max_len = N)]`, past which a string is cut short
+    pub(crate) max_len: Option<usize>,
+}
+
+// How much of a `#[debug(redact)]` field is still shown
+#[derive(Clone, Copy)]
+pub(crate) enum Redact {
+    // Only a fixed placeholder, from `#[debug(redact)]`
+    Placeholder,
+    // The length of the string, vector or slice, from `#[debug(redact = "len")]`
+    Len,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field_attrs = FieldAttrs::default();
+        // Each of the options that replace how the value itself is written, along with where it
+        // was given. `each` and `max_items` work together, everything else is exclusive.
+        let mut renderings: Vec<(&str, Span)> = vec![];
+        let mut seen_keys = SeenKeys::default();
+
+        for attr in debug_attrs(attrs) {
+            // `#[debug = "..."]` is a shorthand for `#[debug(fmt = "...")]`
+            if let syn::Meta::NameValue(name_value) = &attr.meta {
+                seen_keys.insert(&syn::parse_quote_spanned!(name_value.eq_token.span=> fmt))?;
+                let format = lit_str(&name_value.value)?;
+                format::validate(&format)?;
+                renderings.push(("a format string", format.span()));
+                field_attrs.format = Some(format);
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                seen_keys.insert(&meta.path)?;
+
+                if meta.path.is_ident("fmt") {
+                    let format = lit_str(&meta.value()?.parse()?)?;
+                    format::validate(&format)?;
+                    renderings.push(("a format string", format.span()));
+                    field_attrs.format = Some(format);
+                } else if meta.path.is_ident("alt") {
+                    let alt = lit_str(&meta.value()?.parse()?)?;
+                    format::validate(&alt)?;
+                    field_attrs.alt = Some(alt);
+                } else if meta.path.is_ident("bound") {
+                    field_attrs.bound = Some(parse_bounds(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                } else if meta.path.is_ident("skip_if") {
+                    field_attrs.skip_if = Some(parse_lit_str(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("redact") {
+                    renderings.push(("`redact`", meta.path.span()));
+                    field_attrs.redact = Some(if meta.input.peek(Token![=]) {
+                        let lit = lit_str(&meta.value()?.parse()?)?;
+                        if lit.value() != "len" {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `redact = \"len\"`",
+                            ));
+                        }
+                        Redact::Len
+                    } else {
+                        Redact::Placeholder
+                    });
+                } else if meta.path.is_ident("with") {
+                    renderings.push(("`with`", meta.path.span()));
+                    field_attrs.with = Some(parse_lit_str(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("rename") {
+                    field_attrs.rename = Some(lit_str(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("flatten") {
+                    field_attrs.flatten = true;
+                } else if meta.path.is_ident("each") {
+                    if field_attrs.max_items.is_none() {
+                        renderings.push(("`each`", meta.path.span()));
+                    }
+                    let each = lit_str(&meta.value()?.parse()?)?;
+                    format::validate(&each)?;
+                    field_attrs.each = Some(each);
+                } else if meta.path.is_ident("max_items") {
+                    if field_attrs.each.is_none() {
+                        renderings.push(("`max_items`", meta.path.span()));
+                    }
+                    field_attrs.max_items = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else if meta.path.is_ident("max_len") {
+                    renderings.push(("`max_len`", meta.path.span()));
+                    field_attrs.max_len = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else {
+                    return Err(meta.error(
+                        "expected `debug = \"...\"` or one of `fmt`, `alt`, `bound`, `skip`, \
+                         `skip_if`, `redact`, `with`, `rename`, `flatten`, `each`, `max_items`, \
+                         `max_len` inside `debug(...)`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        // A field is written in exactly one way, so allowing several of them would only hide a
+        // mistake. The fields of a flattened value are written under their own names and ways.
+        if let [(first, _), (second, span), ..] = renderings[..] {
+            return Err(syn::Error::new(
+                span,
+                format!("{} cannot be combined with {}", second, first),
+            ));
+        }
+        if let (true, Some((rendering, span))) = (field_attrs.flatten, renderings.first()) {
+            return Err(syn::Error::new(
+                *span,
+                format!("{} cannot be combined with `flatten`", rendering),
+            ));
+        }
+        // The alternate format only replaces a format string, which is what it falls back to
+        if let (Some(alt), None) = (&field_attrs.alt, &field_attrs.format) {
+            return Err(syn::Error::new_spanned(
+                alt,
+                "`alt` requires a format string from `fmt` or `debug = \"...\"`",
+            ));
+        }
+        if let (true, Some(rename)) = (field_attrs.flatten, &field_attrs.rename) {
+            return Err(syn::Error::new_spanned(
+                rename,
+                "`rename` cannot be combined with `flatten`",
+            ));
+        }
+
+        Ok(field_attrs)
+    }
+
+    // Applies the first of the container's `format_type` presets naming `ty`, unless the field
+    // already says how it is written. A preset for `u8` is also used for every element of a
+    // `[u8; N]`, `[u8]`, `Vec<u8>` or `VecDeque<u8>`, as if given through `each`.
+    pub(crate) fn apply_format_types(&mut self, ty: &Type, format_types: &[(syn::Path, LitStr)]) {
+        if self.renders_custom() || self.flatten || format_types.is_empty() {
+            return;
+        }
+
+        let preset = |ty: &Type| {
+            format_types
+                .iter()
+                .find(|(name, _)| type_named(ty, name))
+                .map(|(_, format)| format.clone())
+        };
+
+        if let Some(format) = preset(ty) {
+            self.format = Some(format);
+        } else if let Some(element) = element_type(ty) {
+            self.each = preset(element);
+        }
+    }
+
+    // Whether the field is written through a format string, `debug(redact)`,
+    // `debug(with = "...")` or one of the collection and string options rather than its own
+    // `Debug` impl
+    pub(crate) fn renders_custom(&self) -> bool {
+        self.format.is_some()
+            || self.redact.is_some()
+            || self.with.is_some()
+            || self.each.is_some()
+            || self.max_items.is_some()
+            || self.max_len.is_some()
+    }
+
+    // Whether the field's own `Debug` impl is never used, in which case no bound is inferred
+    // from its type
+    pub(crate) fn needs_no_bound(&self) -> bool {
+        self.skip || self.redact.is_some() || self.with.is_some()
+    }
+}
+
+// The keys given so far in the `#[debug ...]` attributes of a single item, as each key may only
+// be given once
+#[derive(Default)]
+struct SeenKeys {
+    keys: Vec<String>,
+}
+
+impl SeenKeys {
+    fn insert(&mut self, path: &syn::Path) -> syn::Result<()> {
+        let key = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::");
+
+        if self.keys.contains(&key) {
+            let message = match key.as_str() {
+                "fmt" => "the format string is given more than once".to_owned(),
+                _ => format!("`{}` is given more than once", key),
+            };
+            return Err(syn::Error::new_spanned(path, message));
+        }
+
+        self.keys.push(key);
+        Ok(())
+    }
+}
+
+fn debug_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("debug"))
+}
+
+fn lit_str(expr: &syn::Expr) -> syn::Result<LitStr> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit_str), .. }) => Ok(lit_str.clone()),
+        _ => Err(syn::Error::new_spanned(expr, "expected a string literal")),
+    }
+}
+
+// Parses the code inside a string literal. Errors are reported on the whole literal, as where
+// they land within it otherwise depends on the version of syn, such as the end of the input
+// being reported on the derive itself.
+fn parse_lit_str<T: Parse>(expr: &syn::Expr) -> syn::Result<T> {
+    let lit = lit_str(expr)?;
+    lit.parse().map_err(|err| syn::Error::new(lit.span(), err))
+}
+
+// Parses the comma separated where predicates inside a `bound = "..."` literal. An empty string
+// is accepted and means that no bounds are needed at all.
+fn parse_bounds(expr: &syn::Expr) -> syn::Result<Bounds> {
+    let lit = lit_str(expr)?;
+    lit.parse_with(Bounds::parse_terminated).map_err(|err| syn::Error::new(lit.span(), err))
+}
+
+// Whether `ty` is a plain path type named `name`. Macros cannot resolve names, so a single
+// identifier such as `Duration` matches the last segment of `std::time::Duration` too.
+fn type_named(ty: &Type, name: &syn::Path) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    if type_path.qself.is_some()
+        || type_path.path.segments.iter().any(|segment| !segment.arguments.is_none())
+    {
+        return false;
+    }
+
+    let segments = &type_path.path.segments;
+    match name.get_ident() {
+        Some(ident) => segments.last().is_some_and(|segment| segment.ident == *ident),
+        None => {
+            segments.len() == name.segments.len()
+                && segments.iter().zip(&name.segments).all(|(a, b)| a.ident == b.ident)
+        }
+    }
+}
+
+// Returns the element type of an array, a slice or a reference to one, a `Vec` or a `VecDeque`
+fn element_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Array(array) => Some(&array.elem),
+        Type::Slice(slice) => Some(&slice.elem),
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Slice(slice) => Some(&slice.elem),
+            _ => None,
+        },
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "Vec" && segment.ident != "VecDeque" {
+                return None;
+            }
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+                    Some(syn::GenericArgument::Type(element)) => Some(element),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,123 @@
+// Inference of the `Debug` bounds, or bounds on other formatting traits used by a container
+// template, required by the types of the fields.
+//
+// Macros cannot resolve names, so rather than bounding every field type as a whole (see
+// `tests/06-bound-trouble.rs` for why that does not work), each field type is walked down to the
+// places where type parameters of the input appear. A type parameter `T` used in there needs
+// `T: Debug`, while an associated type such as `T::Item` needs `T::Item: Debug` without
+// requiring anything from `T` itself.
+
+use syn::visit::{self, Visit};
+use syn::{Ident, Path, Type, WherePredicate};
+
+// Returns the bounds on `bound_trait` required by a field of type `ty`, given the type parameters
+// of the input
+pub(crate) fn infer_bounds(
+    ty: &Type,
+    type_params: &[Ident],
+    bound_trait: &Path,
+) -> Vec<WherePredicate> {
+    let mut collector = BoundCollector {
+        type_params,
+        bound_trait,
+        bounds: vec![],
+    };
+    collector.visit_type(ty);
+    collector.bounds
+}
+
+// Returns the key and value types of a `HashMap` or a `BTreeMap`, or of a reference to one
+pub(crate) fn map_entry_types(ty: &Type) -> Option<(&Type, &Type)> {
+    match ty {
+        Type::Reference(reference) => map_entry_types(&reference.elem),
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "HashMap" && segment.ident != "BTreeMap" {
+                return None;
+            }
+            let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+                return None;
+            };
+            let mut types = arguments.args.iter().filter_map(|argument| match argument {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+            Some((types.next()?, types.next()?))
+        }
+        _ => None,
+    }
+}
+
+// Whether any of `type_params` is used in `ty`
+pub(crate) fn mentions_type_params(ty: &Type, type_params: &[Ident]) -> bool {
+    let bound_trait = syn::parse_quote!(std::fmt::Debug);
+    !infer_bounds(ty, type_params, &bound_trait).is_empty()
+}
+
+struct BoundCollector<'a> {
+    type_params: &'a [Ident],
+    bound_trait: &'a Path,
+    bounds: Vec<WherePredicate>,
+}
+
+impl BoundCollector<'_> {
+    fn push(&mut self, bounded_ty: &syn::TypePath) {
+        let bound_trait = self.bound_trait;
+        let bound = syn::parse_quote!(#bounded_ty: #bound_trait);
+        if !self.bounds.contains(&bound) {
+            self.bounds.push(bound);
+        }
+    }
+
+    fn is_type_param(&self, ident: &Ident) -> bool {
+        self.type_params.iter().any(|type_param| type_param == ident)
+    }
+}
+
+impl<'ast> Visit<'ast> for BoundCollector<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast syn::TypePath) {
+        // A qualified projection, such as `<T as Iterator>::Item`, is bounded as a whole
+        if let Some(qself) = &type_path.qself {
+            if mentions_type_params(&qself.ty, self.type_params) {
+                self.push(type_path);
+            }
+            return;
+        }
+
+        let path = &type_path.path;
+        let first_segment = &path.segments[0];
+
+        // Either the type parameter itself or one of its associated types, such as `T::Value`.
+        // In the latter case `T` does not need to implement `Debug`, so there is no recursion.
+        if path.leading_colon.is_none() && self.is_type_param(&first_segment.ident) {
+            self.push(type_path);
+            return;
+        }
+
+        // `PhantomData<T>` implements `Debug` whatever `T` is, so nothing inside it is bounded
+        if path.segments.last().is_some_and(|segment| segment.ident == "PhantomData") {
+            return;
+        }
+
+        visit::visit_type_path(self, type_path);
+    }
+
+    fn visit_type_array(&mut self, type_array: &'ast syn::TypeArray) {
+        // The length is an expression, which does not need to implement anything
+        self.visit_type(&type_array.elem);
+    }
+
+    // Raw pointers and function pointers implement `Debug` regardless of the types they point to
+    // or take, while the implementations for trait objects and `impl Trait` cannot be inferred
+    // from their bounds
+    fn visit_type_ptr(&mut self, _: &'ast syn::TypePtr) {}
+
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+
+    fn visit_type_trait_object(&mut self, _: &'ast syn::TypeTraitObject) {}
+
+    fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {}
+
+    // Macros cannot be looked into before they are expanded
+    fn visit_type_macro(&mut self, _: &'ast syn::TypeMacro) {}
+}
@@ -0,0 +1,116 @@
+// The `CustomDisplay` derive, which writes a struct or each variant of an enum through a
+// `#[display("...")]` template.
+//
+// Templates work as the `#[debug(fmt = "...")]` ones of `CustomDebug` do and share its code, so
+// `{host}` writes a field with `Display` and `{host:?}` with `Debug`, and the bounds inferred for
+// the fields follow the traits used by the placeholders.
+
+use crate::attr::{ContainerAttrs, DisplayAttrs, Target};
+use crate::{debug_arm, infer_where_predicates, trace, with_adapter, DebugVariant, Impl};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+pub(crate) fn expand(mut syn_tree: syn::DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &syn_tree.ident;
+    let target = match syn_tree.data {
+        syn::Data::Enum(_) => Target::Enum,
+        _ => Target::Struct,
+    };
+    let display_attrs = DisplayAttrs::parse(&syn_tree.attrs, target)?;
+
+    let variants = match &syn_tree.data {
+        syn::Data::Struct(struct_tree) => {
+            vec![display_variant(
+                quote! { Self },
+                ident,
+                &struct_tree.fields,
+                display_attrs.template,
+            )?]
+        }
+        syn::Data::Enum(enum_tree) => enum_tree
+            .variants
+            .iter()
+            .map(|enum_variant| {
+                let variant_ident = &enum_variant.ident;
+                let variant_attrs = DisplayAttrs::parse(&enum_variant.attrs, Target::Variant)?;
+                display_variant(
+                    quote! { Self::#variant_ident },
+                    variant_ident,
+                    &enum_variant.fields,
+                    variant_attrs.template,
+                )
+            })
+            .collect::<syn::Result<_>>()?,
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`CustomDisplay` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let generic_idents: Vec<syn::Ident> = syn_tree
+        .generics
+        .type_params()
+        .map(|generic_ty| generic_ty.ident.clone())
+        .collect();
+    let bounds = infer_where_predicates(
+        &variants,
+        &generic_idents,
+        display_attrs.bound.as_ref(),
+        Impl::Display,
+    );
+
+    let match_arms: TokenStream2 = variants.iter().map(debug_arm).collect();
+    let match_self = if variants.is_empty() {
+        quote! { *self }
+    } else {
+        quote! { self }
+    };
+    let debug_with = with_adapter(&variants);
+
+    let ident = ident.clone();
+    syn_tree.generics.make_where_clause().predicates.extend(bounds.iter().cloned());
+    let (impl_generics, ty_generics, where_clause) = syn_tree.generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics std::fmt::Display for #ident #ty_generics #where_clause
+        {
+            fn fmt(
+                &self,
+                f: &mut std::fmt::Formatter<'_>,
+            ) -> std::result::Result<(), std::fmt::Error> {
+                #debug_with
+
+                match #match_self {
+                    #match_arms
+                }
+            }
+        }
+    };
+
+    trace::write("CustomDisplay", &ident, &[("Display", &bounds)], &expanded)?;
+
+    Ok(expanded)
+}
+
+// Only the template of the shared container options is used, and a unit variant without one is
+// written as its name
+fn display_variant<'a>(
+    path: TokenStream2,
+    ident: &'a syn::Ident,
+    fields: &'a syn::Fields,
+    template: Option<syn::LitStr>,
+) -> syn::Result<DebugVariant<'a>> {
+    if template.is_none() && !fields.is_empty() {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "missing `#[display(\"...\")]` template for `CustomDisplay`",
+        ));
+    }
+    let attrs = ContainerAttrs {
+        fmt: template,
+        ..ContainerAttrs::default()
+    };
+    DebugVariant::new(path, ident, fields, &attrs, None, &[])
+}
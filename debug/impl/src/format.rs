@@ -0,0 +1,361 @@
+// Parsing of the format strings given through `#[debug = "..."]` and `#[debug(fmt = "...")]`.
+//
+// A field's format string ends up in `format_args!(#debug_fmt, #binding)` with the field as its
+// only argument. Mistakes such as a missing placeholder or a second one would otherwise be
+// reported by `format_args!` somewhere inside the expansion, so the string is parsed here first
+// and every error points at the attribute's literal instead.
+//
+// A container's template refers to the fields by name, or by position for tuple structs. It is
+// rewritten such that every placeholder refers to the binding of its field, which is then passed
+// as a named argument.
+
+use std::fmt::Write;
+use syn::LitStr;
+
+// The traits that can follow the `:` of a placeholder, such as the `x?` in `{:#x?}`
+const FORMAT_TRAITS: &[&str] = &["", "?", "x?", "X?", "x", "X", "o", "b", "e", "E", "p"];
+
+enum Piece {
+    // Text outside of the placeholders, with `{{` and `}}` still escaped
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+struct Placeholder {
+    argument: Argument,
+    // Everything after the `:`, such as `>#10x?`
+    spec: Option<String>,
+    // The format trait at the end of the spec, such as `x?`
+    format_trait: String,
+    // Arguments used as a width or a precision, such as `1$` or `.*`
+    counts: Vec<Argument>,
+}
+
+enum Argument {
+    Index(usize),
+    Name(String),
+}
+
+// Checks that `lit` is a valid format string which consumes exactly one argument
+pub(crate) fn validate(lit: &LitStr) -> syn::Result<()> {
+    let error = |message: String| syn::Error::new_spanned(lit, message);
+    let mut arguments = vec![];
+
+    for piece in parse(lit)? {
+        if let Piece::Placeholder(placeholder) = piece {
+            arguments.push(placeholder.argument);
+            arguments.extend(placeholder.counts);
+        }
+    }
+
+    if arguments.is_empty() {
+        return Err(error(
+            "format string has no placeholder for the field, such as `{:?}`".to_owned(),
+        ));
+    }
+
+    for argument in arguments {
+        match argument {
+            Argument::Index(0) => {}
+            Argument::Index(index) => {
+                return Err(error(format!(
+                    "format string refers to argument {}, but the field is the only argument",
+                    index
+                )))
+            }
+            Argument::Name(name) => {
+                return Err(error(format!(
+                    "named argument `{}` is not available, the field is passed as the only \
+                     positional argument",
+                    name
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the format trait of each placeholder in an already validated format string, such as
+// `x` for `{:#04x}`
+pub(crate) fn format_traits(lit: &LitStr) -> Vec<String> {
+    let pieces = parse(lit).unwrap_or_default();
+    pieces
+        .into_iter()
+        .filter_map(|piece| match piece {
+            Piece::Placeholder(placeholder) => Some(placeholder.format_trait),
+            Piece::Text(_) => None,
+        })
+        .collect()
+}
+
+// A container template rewritten to refer to the bindings of the fields
+pub(crate) struct Template {
+    pub(crate) format: LitStr,
+    // Index of every field used by the template, along with the format trait each placeholder
+    // uses for it
+    pub(crate) uses: Vec<(usize, String)>,
+}
+
+// Resolves the placeholders of a container template against `fields`, named ones by name and
+// positional ones by index
+pub(crate) fn template(lit: &LitStr, fields: &syn::Fields) -> syn::Result<Template> {
+    let error = |message: String| syn::Error::new_spanned(lit, message);
+    let mut format = String::new();
+    let mut uses = vec![];
+
+    for piece in parse(lit)? {
+        let placeholder = match piece {
+            Piece::Text(text) => {
+                format.push_str(&text);
+                continue;
+            }
+            Piece::Placeholder(placeholder) => placeholder,
+        };
+
+        if !placeholder.counts.is_empty() {
+            return Err(error(
+                "a width or precision in a template must be a number, fields cannot be used \
+                 as `$` or `*` arguments"
+                    .to_owned(),
+            ));
+        }
+
+        let index = match (&placeholder.argument, fields) {
+            (Argument::Name(name), syn::Fields::Named(named_fields)) => named_fields
+                .named
+                .iter()
+                .position(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+                .ok_or_else(|| error(format!("no field named `{}` in template", name)))?,
+            (Argument::Index(index), syn::Fields::Unnamed(unnamed_fields)) => {
+                if *index >= unnamed_fields.unnamed.len() {
+                    return Err(error(format!(
+                        "no field at index {} in template, there are only {}",
+                        index,
+                        unnamed_fields.unnamed.len()
+                    )));
+                }
+                *index
+            }
+            (Argument::Name(name), _) => {
+                return Err(error(format!(
+                    "no field named `{}` in template, fields without names are referred to by \
+                     position such as `{{0}}`",
+                    name
+                )))
+            }
+            (Argument::Index(_), _) => {
+                return Err(error(
+                    "positional placeholder in template, fields are referred to by name such \
+                     as `{field}`"
+                        .to_owned(),
+                ))
+            }
+        };
+
+        write!(format, "{{__self_{}", index).unwrap();
+        if let Some(spec) = &placeholder.spec {
+            write!(format, ":{}", spec).unwrap();
+        }
+        format.push('}');
+        uses.push((index, placeholder.format_trait));
+    }
+
+    Ok(Template {
+        format: LitStr::new(&format, lit.span()),
+        uses,
+    })
+}
+
+// Returns the path of the `std::fmt` trait selected by a placeholder's format trait, such as
+// `std::fmt::LowerHex` for `x`
+pub(crate) fn trait_path(format_trait: &str) -> syn::Path {
+    match format_trait {
+        "" => syn::parse_quote!(std::fmt::Display),
+        "x" => syn::parse_quote!(std::fmt::LowerHex),
+        "X" => syn::parse_quote!(std::fmt::UpperHex),
+        "o" => syn::parse_quote!(std::fmt::Octal),
+        "b" => syn::parse_quote!(std::fmt::Binary),
+        "e" => syn::parse_quote!(std::fmt::LowerExp),
+        "E" => syn::parse_quote!(std::fmt::UpperExp),
+        "p" => syn::parse_quote!(std::fmt::Pointer),
+        _ => syn::parse_quote!(std::fmt::Debug),
+    }
+}
+
+fn parse(lit: &LitStr) -> syn::Result<Vec<Piece>> {
+    let error = |message: String| syn::Error::new_spanned(lit, message);
+    let value = lit.value();
+    let mut parser = FormatParser::default();
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                text.push(c);
+                text.push(c);
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(error(format!(
+                                "unterminated placeholder `{{{}` in format string",
+                                placeholder
+                            )))
+                        }
+                    }
+                }
+                parser.pieces.push(Piece::Text(std::mem::take(&mut text)));
+                let placeholder = parser.placeholder(&placeholder).map_err(error)?;
+                parser.pieces.push(Piece::Placeholder(placeholder));
+            }
+            '}' => {
+                return Err(error(
+                    "unmatched `}` in format string, use `}}` to print a brace".to_owned(),
+                ))
+            }
+            c => text.push(c),
+        }
+    }
+
+    parser.pieces.push(Piece::Text(text));
+    Ok(parser.pieces)
+}
+
+#[derive(Default)]
+struct FormatParser {
+    pieces: Vec<Piece>,
+    // Index given to the next placeholder without an explicit argument
+    next_argument: usize,
+}
+
+impl FormatParser {
+    // Parses the text between the braces of a placeholder, `0:>8.3?` in `{0:>8.3?}`
+    fn placeholder(&mut self, placeholder: &str) -> Result<Placeholder, String> {
+        let (argument, spec) = match placeholder.split_once(':') {
+            Some((argument, spec)) => (argument, Some(spec)),
+            None => (placeholder, None),
+        };
+
+        // A `.*` precision takes its argument before the one of the value itself
+        let mut counts = vec![];
+        let format_trait = match spec {
+            Some(spec) => self.spec(spec, placeholder, &mut counts)?,
+            None => "",
+        };
+
+        if !FORMAT_TRAITS.contains(&format_trait) {
+            return Err(format!(
+                "unknown format trait `{}` in `{{{}}}`, expected one of `?`, `x?`, `X?`, `x`, \
+                 `X`, `o`, `b`, `e`, `E` or `p`",
+                format_trait, placeholder
+            ));
+        }
+
+        let argument = if argument.is_empty() {
+            self.implicit_argument()
+        } else {
+            explicit_argument(argument, placeholder)?
+        };
+
+        Ok(Placeholder {
+            argument,
+            spec: spec.map(str::to_owned),
+            format_trait: format_trait.to_owned(),
+            counts,
+        })
+    }
+
+    fn implicit_argument(&mut self) -> Argument {
+        self.next_argument += 1;
+        Argument::Index(self.next_argument - 1)
+    }
+
+    // Parses `[[fill]align][sign]['#']['0'][width]['.' precision]` and returns the format trait
+    // that follows it
+    fn spec<'s>(
+        &mut self,
+        spec: &'s str,
+        placeholder: &str,
+        counts: &mut Vec<Argument>,
+    ) -> Result<&'s str, String> {
+        let mut rest = spec;
+
+        let mut chars = rest.chars();
+        let first = chars.next();
+        let second = chars.next();
+        if let (Some(fill), Some('<' | '^' | '>')) = (first, second) {
+            rest = &rest[fill.len_utf8() + 1..];
+        } else if let Some('<' | '^' | '>') = first {
+            rest = &rest[1..];
+        }
+
+        rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+        rest = rest.strip_prefix('#').unwrap_or(rest);
+        if rest.starts_with('0') && !rest[1..].starts_with('$') {
+            rest = &rest[1..];
+        }
+
+        rest = count(rest, placeholder, counts)?;
+
+        if let Some(precision) = rest.strip_prefix('.') {
+            rest = match precision.strip_prefix('*') {
+                Some(after) => {
+                    counts.push(self.implicit_argument());
+                    after
+                }
+                None => count(precision, placeholder, counts)?,
+            };
+        }
+
+        Ok(rest)
+    }
+}
+
+// Parses an explicit argument, either a position or a name
+fn explicit_argument(argument: &str, placeholder: &str) -> Result<Argument, String> {
+    if let Ok(index) = argument.parse() {
+        Ok(Argument::Index(index))
+    } else if is_identifier(argument) {
+        Ok(Argument::Name(argument.to_owned()))
+    } else {
+        Err(format!("invalid argument `{}` in `{{{}}}`", argument, placeholder))
+    }
+}
+
+// Parses an optional width or precision, which is a number or a reference to an argument such as
+// `1$`, and returns what follows it
+fn count<'s>(
+    rest: &'s str,
+    placeholder: &str,
+    counts: &mut Vec<Argument>,
+) -> Result<&'s str, String> {
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    let (count, after) = rest.split_at(end);
+
+    match after.strip_prefix('$') {
+        Some(after) if !count.is_empty() => {
+            counts.push(explicit_argument(count, placeholder)?);
+            Ok(after)
+        }
+        // A bare identifier is the format trait rather than a count, as in `{:x}`
+        _ => {
+            let digits = count.bytes().take_while(u8::is_ascii_digit).count();
+            Ok(&rest[digits..])
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
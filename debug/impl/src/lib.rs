@@ -0,0 +1,835 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned,
+    LitStr,
+};
+
+mod attr;
+mod bound;
+mod display;
+mod format;
+mod trace;
+
+use attr::{Bounds, ContainerAttrs, Extra, FieldAttrs, Redact, Style, Target};
+use format::Template;
+
+// The impls generated for the input, which each need their own where predicates
+#[derive(Clone, Copy, PartialEq)]
+enum Impl {
+    Debug,
+    DebugFlatten,
+    DebugFields,
+    Display,
+}
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let syn_tree: syn::DeriveInput = syn::parse_macro_input!(input);
+
+    expand(syn_tree)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// Field options given through `#[debug(...)]`, such as `redact` or `with`, also apply to the
+// fields used by a `#[display("...")]` template
+#[proc_macro_derive(CustomDisplay, attributes(display, debug))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let syn_tree: syn::DeriveInput = syn::parse_macro_input!(input);
+
+    display::expand(syn_tree)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// The struct itself or one of the enum's variants, which gets its own match arm
+struct DebugVariant<'a> {
+    // Path used in the match arm's pattern, such as `Self` or `Self::Variant`
+    path: TokenStream2,
+    ident: &'a syn::Ident,
+    fields: &'a syn::Fields,
+    debug_fields: Vec<DebugField<'a>>,
+    // Template from `debug(fmt = "...")`, written in place of the `debug_struct` output
+    template: Option<Template>,
+    style: Style,
+    // Computed entries from `debug(extra(...))`, written after the fields
+    extras: Vec<Extra>,
+    // Set by `debug(transparent)`, delegates to the `Debug` impl of the only field left written
+    transparent: bool,
+}
+
+struct DebugField<'a> {
+    field: &'a syn::Field,
+    attrs: FieldAttrs,
+    // Each field is bound to `__self_<index>`, such that fields named like the formatter `f`
+    // cannot shadow it
+    binding: syn::Ident,
+    // Name written for the field, from `debug(rename = "...")` or else its identifier. Fields
+    // without one are named after their position where the style needs a name.
+    name: LitStr,
+}
+
+impl<'a> DebugVariant<'a> {
+    fn new(
+        path: TokenStream2,
+        ident: &'a syn::Ident,
+        fields: &'a syn::Fields,
+        attrs: &ContainerAttrs,
+        // The style given on the enum, if this is one of its variants
+        enum_style: Option<Style>,
+        format_types: &[(syn::Path, LitStr)],
+    ) -> syn::Result<Self> {
+        let debug_fields: Vec<DebugField> = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let mut attrs = FieldAttrs::parse(&field.attrs)?;
+                attrs.apply_format_types(&field.ty, format_types);
+                let name = attrs.rename.clone().unwrap_or_else(|| {
+                    let name = field_name(field, idx);
+                    LitStr::new(name.strip_prefix("r#").unwrap_or(&name), field.span())
+                });
+
+                Ok(DebugField {
+                    field,
+                    attrs,
+                    binding: format_ident!("__self_{}", idx),
+                    name,
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+
+        let fmt = attrs.fmt.as_ref();
+        let template = fmt.map(|fmt| format::template(fmt, fields)).transpose()?;
+
+        // A template has no way to mark a field as left out, so it can only use printed fields
+        for (index, _) in template.iter().flat_map(|template| template.uses.iter()) {
+            let attrs = &debug_fields[*index].attrs;
+            if attrs.skip || attrs.skip_if.is_some() {
+                return Err(syn::Error::new_spanned(
+                    fmt,
+                    format!(
+                        "field `{}` is skipped and cannot be used in the template",
+                        field_name(debug_fields[*index].field, *index)
+                    ),
+                ));
+            }
+        }
+
+        // A unit struct or variant with computed entries is written like one with named fields
+        let style = attrs.style.or(enum_style).unwrap_or(match fields {
+            syn::Fields::Named(_) => Style::Struct,
+            syn::Fields::Unit if !attrs.extras.is_empty() => Style::Struct,
+            _ => Style::Tuple,
+        });
+
+        // `debug_tuple` has no names to write next to the computed values
+        if let (Style::Tuple, Some(extra)) = (style, attrs.extras.first()) {
+            return Err(syn::Error::new_spanned(
+                &extra.name,
+                "`debug(extra(...))` is not supported with `debug(style = \"tuple\")`, the \
+                 default for unnamed fields",
+            ));
+        }
+
+        // Only `debug_struct` can take the named fields of a flattened value
+        for debug_field in &debug_fields {
+            if debug_field.attrs.flatten && style != Style::Struct {
+                return Err(syn::Error::new_spanned(
+                    debug_field.field,
+                    "`debug(flatten)` is only supported with `debug(style = \"struct\")`, the \
+                     default for named fields",
+                ));
+            }
+        }
+
+        // Delegating needs exactly one field that is always written, skipped ones being ignored
+        if attrs.transparent {
+            let mut written = debug_fields.iter().filter(|debug_field| !debug_field.attrs.skip);
+            match (written.next(), written.next()) {
+                (None, _) => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "`debug(transparent)` requires a field that is not skipped",
+                    ))
+                }
+                (Some(_), Some(second)) => {
+                    return Err(syn::Error::new_spanned(
+                        second.field,
+                        "`debug(transparent)` requires a single field that is not skipped",
+                    ))
+                }
+                (Some(only), None) if only.attrs.skip_if.is_some() || only.attrs.flatten => {
+                    return Err(syn::Error::new_spanned(
+                        only.field,
+                        "the field of `debug(transparent)` cannot use `skip_if` or `flatten`",
+                    ))
+                }
+                (Some(_), None) => {}
+            }
+        }
+
+        Ok(DebugVariant {
+            path,
+            ident,
+            fields,
+            debug_fields,
+            template,
+            style,
+            extras: attrs.extras.clone(),
+            transparent: attrs.transparent,
+        })
+    }
+}
+
+fn expand(mut syn_tree: syn::DeriveInput) -> syn::Result<TokenStream2> {
+    let struct_ident = syn_tree.ident.clone();
+    let target = match syn_tree.data {
+        syn::Data::Enum(_) => Target::Enum,
+        _ => Target::Struct,
+    };
+    let container_attrs = ContainerAttrs::parse(&syn_tree.attrs, target)?;
+
+    let variants = match &syn_tree.data {
+        syn::Data::Struct(struct_tree) => {
+            vec![DebugVariant::new(
+                quote! { Self },
+                &syn_tree.ident,
+                &struct_tree.fields,
+                &container_attrs,
+                None,
+                &container_attrs.format_types,
+            )?]
+        }
+        syn::Data::Enum(enum_tree) => enum_tree
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_ident = &variant.ident;
+                let variant_attrs = ContainerAttrs::parse(&variant.attrs, Target::Variant)?;
+                DebugVariant::new(
+                    quote! { Self::#variant_ident },
+                    variant_ident,
+                    &variant.fields,
+                    &variant_attrs,
+                    container_attrs.style,
+                    &container_attrs.format_types,
+                )
+            })
+            .collect::<syn::Result<_>>()?,
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &syn_tree.ident,
+                "`CustomDebug` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let generic_idents: Vec<syn::Ident> = syn_tree
+        .generics
+        .type_params()
+        .map(|generic_ty| generic_ty.ident.clone())
+        .collect();
+
+    let where_predicates = |generated: Impl| {
+        infer_where_predicates(
+            &variants,
+            &generic_idents,
+            container_attrs.bound.as_ref(),
+            generated,
+        )
+    };
+    let debug_bounds = where_predicates(Impl::Debug);
+    let flatten_bounds = where_predicates(Impl::DebugFlatten);
+    let fields_bounds = where_predicates(Impl::DebugFields);
+
+    let match_arms: TokenStream2 = variants.iter().map(debug_arm).collect();
+    let flatten_arms: TokenStream2 = variants.iter().map(flatten_arm).collect();
+
+    // An enum without variants can never be instantiated, so there is nothing to match on
+    let match_self = if variants.is_empty() {
+        quote! { *self }
+    } else {
+        quote! { self }
+    };
+
+    let debug_with = with_adapter(&variants);
+
+    let mut debug_generics = syn_tree.generics.clone();
+    debug_generics.make_where_clause().predicates.extend(debug_bounds.iter().cloned());
+    let mut flatten_generics = syn_tree.generics.clone();
+    flatten_generics.make_where_clause().predicates.extend(flatten_bounds.iter().cloned());
+    syn_tree.generics.make_where_clause().predicates.extend(fields_bounds.iter().cloned());
+
+    // Identify if the generics from the struct definitions have the `Debug` trait bound
+    let (impl_generics, ty_generics, where_clause) = debug_generics.split_for_impl();
+    let (_, _, flatten_where_clause) = flatten_generics.split_for_impl();
+    let (_, _, fields_where_clause) = syn_tree.generics.split_for_impl();
+
+    let debug_fields_impl = if container_attrs.visitor {
+        let visit_arms: TokenStream2 = variants.iter().map(visit_arm).collect();
+        quote! {
+            impl #impl_generics ::derive_debug::DebugFields for #struct_ident #ty_generics
+                #fields_where_clause
+            {
+                fn visit_fields(&self, visitor: &mut dyn ::derive_debug::FieldVisitor) {
+                    #debug_with
+
+                    match #match_self {
+                        #visit_arms
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Flattening writes every field with its own rendering under its own name. A template does
+    // not require that rendering to exist, such that a field only used as `{x}` may not implement
+    // `Debug`, while unnamed fields, including the one of a transparent newtype, have no name
+    // that would make sense among the fields of the parent.
+    let flattenable = variants.iter().all(|variant| {
+        variant.template.is_none()
+            && !variant.transparent
+            && !matches!(variant.fields, syn::Fields::Unnamed(_))
+    });
+    let debug_flatten_impl = if flattenable {
+        quote! {
+            impl #impl_generics ::derive_debug::DebugFlatten for #struct_ident #ty_generics
+                #flatten_where_clause
+            {
+                fn flatten_fields(
+                    &self,
+                    debug_builder: &mut std::fmt::DebugStruct<'_, '_>,
+                ) -> bool {
+                    #debug_with
+
+                    match #match_self {
+                        #flatten_arms
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        #debug_fields_impl
+
+        #debug_flatten_impl
+
+        impl #impl_generics std::fmt::Debug for #struct_ident #ty_generics #where_clause
+        {
+            fn fmt(
+                &self,
+                f: &mut std::fmt::Formatter<'_>,
+            ) -> std::result::Result<(), std::fmt::Error> {
+                #debug_with
+
+                match #match_self {
+                    #match_arms
+                }
+            }
+        }
+    };
+
+    let mut traced_bounds = vec![("Debug", &debug_bounds)];
+    if flattenable {
+        traced_bounds.push(("DebugFlatten", &flatten_bounds));
+    }
+    if container_attrs.visitor {
+        traced_bounds.push(("DebugFields", &fields_bounds));
+    }
+    trace::write("CustomDebug", &struct_ident, &traced_bounds, &expanded)?;
+
+    Ok(expanded)
+}
+
+// Fields with a `debug(with = "...")` function are wrapped in this adapter, such that the function
+// can be passed to the builders like any other `Debug` value, or to a template as a `Display` one
+fn with_adapter(variants: &[DebugVariant<'_>]) -> TokenStream2 {
+    if variants
+        .iter()
+        .flat_map(|variant| variant.debug_fields.iter())
+        .any(|debug_field| debug_field.attrs.with.is_some())
+    {
+        quote! {
+            struct DebugWith<F>(F)
+            where
+                F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+            impl<F> std::fmt::Debug for DebugWith<F>
+            where
+                F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+            {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    (self.0)(f)
+                }
+            }
+
+            impl<F> std::fmt::Display for DebugWith<F>
+            where
+                F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+            {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    (self.0)(f)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    }
+}
+
+// Generates the match arm that destructures a variant and writes its fields in its style, by
+// default `debug_struct` for named fields and `debug_tuple` for unnamed ones. A unit struct or
+// variant is written as just its name.
+fn debug_arm(variant: &DebugVariant<'_>) -> TokenStream2 {
+    let path = &variant.path;
+    let debug_ident = LitStr::new(&variant.ident.to_string(), variant.ident.span());
+
+    // Skipped fields are not bound at all, such that they do not trigger unused variable warnings
+    let bindings: Vec<TokenStream2> = variant
+        .debug_fields
+        .iter()
+        .map(|debug_field| {
+            if debug_field.attrs.skip {
+                quote! { _ }
+            } else {
+                let binding = &debug_field.binding;
+                quote! { #binding }
+            }
+        })
+        .collect();
+
+    let pattern = match variant.fields {
+        syn::Fields::Named(named_fields) => {
+            let field_idents = named_fields.named.iter().map(|field| &field.ident);
+            quote! { #path { #(#field_idents: #bindings),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { #path(#(#bindings),*) },
+        syn::Fields::Unit if variant.template.is_none() && variant.extras.is_empty() => {
+            return quote! { #path => f.write_str(#debug_ident), }
+        }
+        syn::Fields::Unit => quote! { #path },
+    };
+
+    // Every field used by the template is passed once as a named argument, its binding being the
+    // name the rewritten template refers to it by
+    if let Some(template) = &variant.template {
+        let format = &template.format;
+        let mut arguments = TokenStream2::new();
+        let mut passed = vec![];
+        for (index, _) in &template.uses {
+            if passed.contains(index) {
+                continue;
+            }
+            passed.push(*index);
+
+            let debug_field = &variant.debug_fields[*index];
+            let binding = &debug_field.binding;
+            let value = custom_value(debug_field).unwrap_or_else(|| quote! { #binding });
+            arguments.extend(quote! { , #binding = #value });
+        }
+
+        return quote! {
+            #pattern => std::write!(f, #format #arguments),
+        };
+    }
+
+    // The formatter is passed on as is, such that the field also follows `{:#?}` and the other
+    // flags given for the whole value
+    if variant.transparent {
+        let debug_field = variant
+            .debug_fields
+            .iter()
+            .find(|debug_field| !debug_field.attrs.skip)
+            .expect("checked when the variant is parsed");
+        let binding = &debug_field.binding;
+        let value = custom_value(debug_field).unwrap_or_else(|| quote! { #binding });
+        return quote! {
+            #pattern => std::fmt::Debug::fmt(#value, f),
+        };
+    }
+
+    let debug_builder = match variant.style {
+        Style::Struct => quote! { let debug_builder = &mut f.debug_struct(#debug_ident); },
+        Style::Tuple => quote! { let debug_builder = &mut f.debug_tuple(#debug_ident); },
+        Style::Map => quote! { let debug_builder = &mut f.debug_map(); },
+        Style::Compact => quote! { f.write_str(#debug_ident)?; },
+    };
+
+    // The output ends with `..` whenever a field was left out of it, either always because of a
+    // `debug(skip)` or only when the predicate of a `debug(skip_if = "...")` held or a flattened
+    // value left out some of its own fields. In the latter case this is tracked at runtime
+    // through `exhaustive`.
+    let always_skips = variant.debug_fields.iter().any(|debug_field| debug_field.attrs.skip);
+    let may_skip = !always_skips
+        && variant.debug_fields.iter().any(|debug_field| {
+            debug_field.attrs.skip_if.is_some() || debug_field.attrs.flatten
+        });
+
+    let fields_write_calls = fields_write_calls(variant, variant.style, may_skip);
+
+    let (finish, finish_non_exhaustive) = match variant.style {
+        Style::Struct | Style::Tuple | Style::Map => (
+            quote! { debug_builder.finish() },
+            quote! { debug_builder.finish_non_exhaustive() },
+        ),
+        Style::Compact => (quote! { Ok(()) }, quote! { f.write_str(" ..") }),
+    };
+
+    let finish = if always_skips {
+        finish_non_exhaustive
+    } else if may_skip {
+        quote! {
+            if exhaustive {
+                #finish
+            } else {
+                #finish_non_exhaustive
+            }
+        }
+    } else {
+        finish
+    };
+
+    let exhaustive = if may_skip {
+        quote! { let mut exhaustive = true; }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        #pattern => {
+            #debug_builder
+            #exhaustive
+            #fields_write_calls
+            #finish
+        }
+    }
+}
+
+// Generates the match arm of `DebugFlatten::flatten_fields`, which writes the fields of a variant
+// into the `debug_struct` of the parent whatever the variant's own style or template is
+fn flatten_arm(variant: &DebugVariant<'_>) -> TokenStream2 {
+    let path = &variant.path;
+    let pattern = match variant.fields {
+        syn::Fields::Named(named_fields) => {
+            let field_idents = named_fields.named.iter().map(|field| &field.ident);
+            let bindings = variant.debug_fields.iter().map(|debug_field| &debug_field.binding);
+            quote! { #path { #(#field_idents: #bindings),* } }
+        }
+        syn::Fields::Unnamed(_) => {
+            let bindings = variant.debug_fields.iter().map(|debug_field| &debug_field.binding);
+            quote! { #path(#(#bindings),*) }
+        }
+        syn::Fields::Unit if variant.extras.is_empty() => return quote! { #path => true, },
+        syn::Fields::Unit => quote! { #path },
+    };
+
+    let always_skips = variant.debug_fields.iter().any(|debug_field| debug_field.attrs.skip);
+    let fields_write_calls = fields_write_calls(variant, Style::Struct, true);
+
+    quote! {
+        #pattern => {
+            let mut exhaustive = !#always_skips;
+            #fields_write_calls
+            exhaustive
+        }
+    }
+}
+
+// Generates the match arm of `DebugFields::visit_fields`, which passes every printed field of a
+// variant to the visitor under the same name and with the same rendering as the `Debug` impl
+fn visit_arm(variant: &DebugVariant<'_>) -> TokenStream2 {
+    let path = &variant.path;
+    let bindings = variant.debug_fields.iter().map(|debug_field| &debug_field.binding);
+    let pattern = match variant.fields {
+        syn::Fields::Named(named_fields) => {
+            let field_idents = named_fields.named.iter().map(|field| &field.ident);
+            quote! { #path { #(#field_idents: #bindings),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { #path(#(#bindings),*) },
+        syn::Fields::Unit => quote! { #path },
+    };
+
+    let mut visit_calls = TokenStream2::new();
+
+    for debug_field in variant.debug_fields.iter() {
+        if debug_field.attrs.skip {
+            continue;
+        }
+
+        let binding = &debug_field.binding;
+        let name = &debug_field.name;
+        let value = custom_value(debug_field).unwrap_or_else(|| quote! { #binding });
+        let visit_call = if debug_field.attrs.flatten {
+            quote! { ::derive_debug::DebugFields::visit_fields(#binding, visitor); }
+        } else {
+            quote! { visitor.field(#name, #value); }
+        };
+
+        visit_calls.extend(match &debug_field.attrs.skip_if {
+            Some(skip_if) => quote! {
+                if !(#skip_if)(#binding) {
+                    #visit_call
+                }
+            },
+            None => visit_call,
+        });
+    }
+
+    for Extra { name, expr } in &variant.extras {
+        visit_calls.extend(quote! { visitor.field(#name, &(#expr)); });
+    }
+
+    quote! {
+        #pattern => {
+            #visit_calls
+        }
+    }
+}
+
+// Generates the statements writing each field of a variant into `debug_builder`, or straight
+// into `f` for the compact style. With `may_skip`, fields left out at runtime clear `exhaustive`.
+fn fields_write_calls(variant: &DebugVariant<'_>, style: Style, may_skip: bool) -> TokenStream2 {
+    let mut fields_write_calls = TokenStream2::new();
+
+    for debug_field in variant.debug_fields.iter() {
+        if debug_field.attrs.skip {
+            continue;
+        }
+
+        let binding = &debug_field.binding;
+        let value = custom_value(debug_field).unwrap_or_else(|| {
+            let debug_fmt = LitStr::new("{:?}", debug_field.field.span());
+            quote! { &std::format_args!(#debug_fmt, #binding) }
+        });
+
+        let name = &debug_field.name;
+        let write_call = match style {
+            Style::Struct if debug_field.attrs.flatten && may_skip => quote! {
+                if !::derive_debug::DebugFlatten::flatten_fields(#binding, debug_builder) {
+                    exhaustive = false;
+                }
+            },
+            Style::Struct if debug_field.attrs.flatten => quote! {
+                ::derive_debug::DebugFlatten::flatten_fields(#binding, debug_builder);
+            },
+            Style::Struct => quote! { debug_builder.field(#name, #value); },
+            Style::Tuple => quote! { debug_builder.field(#value); },
+            Style::Map => quote! { debug_builder.entry(&#name, #value); },
+            // The value goes through a fresh `{:?}`, which drops the `{:#?}` flag of `f`
+            Style::Compact => quote! { std::write!(f, " {}={:?}", #name, #value)?; },
+        };
+
+        fields_write_calls.extend(match &debug_field.attrs.skip_if {
+            Some(skip_if) if may_skip => quote! {
+                if (#skip_if)(#binding) {
+                    exhaustive = false;
+                } else {
+                    #write_call
+                }
+            },
+            Some(skip_if) => quote! {
+                if !(#skip_if)(#binding) {
+                    #write_call
+                }
+            },
+            None => write_call,
+        });
+    }
+
+    // Computed entries are always written, so they never clear `exhaustive`
+    for Extra { name, expr } in &variant.extras {
+        fields_write_calls.extend(match style {
+            Style::Struct => quote! { debug_builder.field(#name, &(#expr)); },
+            Style::Map => quote! { debug_builder.entry(&#name, &(#expr)); },
+            Style::Compact => quote! { std::write!(f, " {}={:?}", #name, #expr)?; },
+            // Rejected when the variant is parsed
+            Style::Tuple => unreachable!(),
+        });
+    }
+
+    fields_write_calls
+}
+
+// Infers the where predicates of one of the generated impls, of which only the `Debug` and
+// `Display` ones take templates into account.
+//
+// A `debug(bound = "...")` on the container disables the inference entirely, while one on a
+// field only replaces the bounds that would be inferred from that field's type. Fields that are
+// never printed, or whose value is redacted or written by a `debug(with = "...")` function, do
+// not need any bound. A plain field used by a template needs the trait its placeholder formats it
+// with, such as `Display` for `{x}`, while a field with its own format string, `each` or `alt`
+// needs the traits of the placeholders in there, such as `LowerHex` for `{:#x}`. A string cut
+// short by `max_len` needs `AsRef<str>`. A flattened field needs its type to implement
+// `DebugFlatten`, or `DebugFields` for the impl of that trait.
+fn infer_where_predicates(
+    variants: &[DebugVariant<'_>],
+    generic_idents: &[syn::Ident],
+    container_bound: Option<&Bounds>,
+    generated: Impl,
+) -> Vec<syn::WherePredicate> {
+    if let Some(bound) = container_bound {
+        return bound.iter().cloned().collect();
+    }
+
+    let debug_trait: syn::Path = syn::parse_quote!(std::fmt::Debug);
+    let mut field_uses = vec![];
+    for variant in variants {
+        match &variant.template {
+            Some(template) if matches!(generated, Impl::Debug | Impl::Display) => {
+                for (index, format_trait) in &template.uses {
+                    let debug_field = &variant.debug_fields[*index];
+                    if debug_field.attrs.renders_custom() {
+                        field_uses.push((debug_field, debug_trait.clone()));
+                    } else if format_trait == "p" {
+                        // Any `&T` can be formatted with `{:p}`, so there is nothing worth
+                        // inferring for `Pointer`
+                        continue;
+                    } else {
+                        field_uses.push((debug_field, format::trait_path(format_trait)));
+                    }
+                }
+            }
+            _ => field_uses.extend(
+                variant
+                    .debug_fields
+                    .iter()
+                    .map(|debug_field| (debug_field, debug_trait.clone())),
+            ),
+        }
+    }
+
+    let mut bounds = vec![];
+    for (debug_field, bound_trait) in field_uses {
+        let ty = &debug_field.field.ty;
+        let field_bounds = match &debug_field.attrs.bound {
+            Some(bound) => bound.iter().cloned().collect(),
+            None if debug_field.attrs.needs_no_bound() => vec![],
+            // Bounding the whole type is only needed when it depends on the type parameters,
+            // otherwise the call to `flatten_fields` checks it
+            None if debug_field.attrs.flatten => {
+                if bound::mentions_type_params(ty, generic_idents) {
+                    match generated {
+                        Impl::DebugFields => {
+                            vec![syn::parse_quote!(#ty: ::derive_debug::DebugFields)]
+                        }
+                        _ => vec![syn::parse_quote!(#ty: ::derive_debug::DebugFlatten)],
+                    }
+                } else {
+                    vec![]
+                }
+            }
+            // A string cut short by `debug(max_len = N)` is only read through `AsRef<str>`, which
+            // the call to `truncated` checks when the type does not depend on type parameters
+            None if debug_field.attrs.max_len.is_some() => {
+                if bound::mentions_type_params(ty, generic_idents) {
+                    vec![syn::parse_quote!(#ty: std::convert::AsRef<str>)]
+                } else {
+                    vec![]
+                }
+            }
+            // A format string writes the field with the traits of its placeholders, whatever
+            // the template writes the formatted string with
+            None if debug_field.attrs.format.is_some() => {
+                let formats = debug_field.attrs.format.iter().chain(&debug_field.attrs.alt);
+                formats
+                    .flat_map(|format| format_bounds(ty, format, generic_idents))
+                    .collect()
+            }
+            // The elements of the collection need the traits used by `each`, except for the keys
+            // of a map, which are always written with `Debug`
+            None => match (&debug_field.attrs.each, bound::map_entry_types(ty)) {
+                (Some(each), Some((key_ty, value_ty))) => {
+                    let mut map_bounds = bound::infer_bounds(key_ty, generic_idents, &debug_trait);
+                    map_bounds.extend(format_bounds(value_ty, each, generic_idents));
+                    map_bounds
+                }
+                (Some(each), None) => format_bounds(ty, each, generic_idents),
+                (None, _) => bound::infer_bounds(ty, generic_idents, &bound_trait),
+            },
+        };
+        for bound in field_bounds {
+            if !bounds.contains(&bound) {
+                bounds.push(bound);
+            }
+        }
+    }
+    bounds
+}
+
+// Infers the bounds required to write a value of type `ty` through the placeholders of `format`,
+// leaving out `{:p}` as any reference implements `Pointer`
+fn format_bounds(
+    ty: &syn::Type,
+    format: &LitStr,
+    generic_idents: &[syn::Ident],
+) -> Vec<syn::WherePredicate> {
+    format::format_traits(format)
+        .iter()
+        .filter(|format_trait| *format_trait != "p")
+        .flat_map(|format_trait| {
+            let format_trait = format::trait_path(format_trait);
+            bound::infer_bounds(ty, generic_idents, &format_trait)
+        })
+        .collect()
+}
+
+// Returns the value written for a field that has its own rendering, through a format string,
+// `debug(redact)` or `debug(with = "...")`. It implements both `Debug` and `Display`.
+fn custom_value(debug_field: &DebugField<'_>) -> Option<TokenStream2> {
+    let binding = &debug_field.binding;
+
+    // The options are exclusive, which is checked while parsing them. A redacted value is never
+    // formatted, such that neither `{:?}` nor `{:#?}` can reveal it.
+    let attrs = &debug_field.attrs;
+    let value = if let Some(redact) = attrs.redact {
+        match redact {
+            Redact::Placeholder => quote! { &std::format_args!("<redacted>") },
+            Redact::Len => quote! { &std::format_args!("<redacted, len {}>", #binding.len()) },
+        }
+    } else if let Some(with) = &attrs.with {
+        quote! { &DebugWith(|f: &mut std::fmt::Formatter<'_>| #with(#binding, f)) }
+    } else if let (Some(debug_fmt), Some(alt)) = (&attrs.format, &attrs.alt) {
+        quote! {
+            &::derive_debug::helpers::alternate(
+                std::format_args!(#debug_fmt, #binding),
+                std::format_args!(#alt, #binding),
+            )
+        }
+    } else if let Some(debug_fmt) = &attrs.format {
+        quote! { &std::format_args!(#debug_fmt, #binding) }
+    } else if let Some(max_len) = attrs.max_len {
+        quote! { &::derive_debug::helpers::truncated(#binding, #max_len) }
+    } else if attrs.each.is_some() || attrs.max_items.is_some() {
+        let max_items = match attrs.max_items {
+            Some(max_items) => quote! { std::option::Option::Some(#max_items) },
+            None => quote! { std::option::Option::None },
+        };
+        let element = match &attrs.each {
+            Some(each) => quote! { std::write!(f, #each, element) },
+            None => quote! { std::fmt::Debug::fmt(element, f) },
+        };
+        quote! {
+            &::derive_debug::helpers::elements(#binding, #max_items, |element, f| #element)
+        }
+    } else {
+        return None;
+    };
+
+    Some(value)
+}
+
+// The name of a field in error messages, its position for a tuple field
+fn field_name(field: &syn::Field, index: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => index.to_string(),
+    }
+}
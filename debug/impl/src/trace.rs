@@ -0,0 +1,102 @@
+// Opt-in trace of what the derives generate, to debug bound inference without reading the
+// output of `cargo expand`.
+//
+// When the `DERIVE_DEBUG_TRACE` environment variable is set at expansion time, the bounds
+// inferred for each impl and the generated code are appended to the file it names. Cargo does
+// not rebuild a crate when only this variable changes, so a `cargo clean -p` of the crate using
+// the derive may be needed to see its trace.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use std::fs::OpenOptions;
+use std::io::Write;
+use syn::{Ident, WherePredicate};
+
+const TRACE_VAR: &str = "DERIVE_DEBUG_TRACE";
+
+pub(crate) fn write(
+    // The derive being traced, `CustomDebug` or `CustomDisplay`
+    derive: &str,
+    ident: &Ident,
+    bounds: &[(&str, &Vec<WherePredicate>)],
+    expanded: &TokenStream2,
+) -> syn::Result<()> {
+    let Some(path) = std::env::var_os(TRACE_VAR) else {
+        return Ok(());
+    };
+
+    let mut trace = format!("// {} for `{}`\n", derive, ident);
+    for (impl_trait, predicates) in bounds {
+        trace.push_str(&format!("// inferred bounds for `{}`:", impl_trait));
+        if predicates.is_empty() {
+            trace.push_str(" none");
+        }
+        for predicate in predicates.iter() {
+            trace.push_str(&format!("\n//     {},", predicate.to_token_stream()));
+        }
+        trace.push('\n');
+    }
+    trace.push_str(&format!("{}\n\n", expanded));
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(trace.as_bytes()))
+        .map_err(|err| {
+            syn::Error::new(
+                ident.span(),
+                format!("cannot write the {} trace to {:?}: {}", TRACE_VAR, path, err),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TRACE_VAR;
+
+    // Both derives are traced into the same file, which is only read once they are done
+    #[test]
+    fn traces_inferred_bounds() {
+        let path = std::env::temp_dir().join(format!("derive-debug-trace-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::env::set_var(TRACE_VAR, &path);
+
+        crate::expand(syn::parse_quote! {
+            struct Wrapper<T, U> {
+                value: T,
+                #[debug(skip)]
+                marker: U,
+            }
+        })
+        .unwrap();
+        crate::display::expand(syn::parse_quote! {
+            #[display("{value:x}")]
+            struct Hex<T> {
+                value: T,
+            }
+        })
+        .unwrap();
+
+        std::env::remove_var(TRACE_VAR);
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let comments: Vec<&str> = trace.lines().filter(|line| line.starts_with("//")).collect();
+        assert_eq!(
+            comments,
+            [
+                "// CustomDebug for `Wrapper`",
+                "// inferred bounds for `Debug`:",
+                "//     T : std :: fmt :: Debug,",
+                "// inferred bounds for `DebugFlatten`:",
+                "//     T : std :: fmt :: Debug,",
+                "// CustomDisplay for `Hex`",
+                "// inferred bounds for `Display`:",
+                "//     T : std :: fmt :: LowerHex,",
+            ],
+        );
+        assert!(trace.contains("impl < T , U > std :: fmt :: Debug for Wrapper < T , U >"));
+        assert!(trace.contains("impl < T > std :: fmt :: Display for Hex < T >"));
+    }
+}
@@ -0,0 +1,297 @@
+// Runtime support for the code generated by `CustomDebug`, which is not meant to be used directly.
+//
+// `#[debug(each = "...")]` and `#[debug(max_items = N)]` write a collection element by element
+// through `elements`, `#[debug(max_len = N)]` writes a string through `truncated`, and
+// `#[debug(fmt = "...", alt = "...")]` picks one of its format strings through `alternate`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+// A collection whose elements can be written one by one. Maps write their keys with `Debug` and
+// only pass their values as elements.
+pub trait Collection {
+    type Element: ?Sized;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&Self::Element, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result;
+}
+
+// Writes `collection` with each element going through `element`, followed by `..` in place of
+// the elements past `max_items`
+pub fn elements<C, F>(collection: &C, max_items: Option<usize>, element: F) -> Elements<'_, C, F>
+where
+    C: Collection + ?Sized,
+    F: Fn(&C::Element, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    Elements {
+        collection,
+        max_items,
+        element,
+    }
+}
+
+pub struct Elements<'a, C: ?Sized, F> {
+    collection: &'a C,
+    max_items: Option<usize>,
+    element: F,
+}
+
+impl<C, F> fmt::Debug for Elements<'_, C, F>
+where
+    C: Collection + ?Sized,
+    F: Fn(&C::Element, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.collection.fmt_elements(f, self.max_items, &self.element)
+    }
+}
+
+impl<C, F> fmt::Display for Elements<'_, C, F>
+where
+    C: Collection + ?Sized,
+    F: Fn(&C::Element, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Writes the first `max_len` characters of `string` with `Debug`, followed by `..` if some were
+// left out
+pub fn truncated<S: AsRef<str> + ?Sized>(string: &S, max_len: usize) -> Truncated<'_> {
+    Truncated {
+        string: string.as_ref(),
+        max_len,
+    }
+}
+
+pub struct Truncated<'a> {
+    string: &'a str,
+    max_len: usize,
+}
+
+impl fmt::Debug for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.string.char_indices().nth(self.max_len) {
+            Some((end, _)) => {
+                fmt::Debug::fmt(&self.string[..end], f)?;
+                f.write_str("..")
+            }
+            None => fmt::Debug::fmt(self.string, f),
+        }
+    }
+}
+
+impl fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Writes `alternate` when the formatter has the `#` flag, as the one passed to each field under
+// `{:#?}` does, and `compact` otherwise
+pub fn alternate<'a>(compact: fmt::Arguments<'a>, alternate: fmt::Arguments<'a>) -> Alternate<'a> {
+    Alternate { compact, alternate }
+}
+
+pub struct Alternate<'a> {
+    compact: fmt::Arguments<'a>,
+    alternate: fmt::Arguments<'a>,
+}
+
+impl fmt::Debug for Alternate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_fmt(self.alternate)
+        } else {
+            f.write_fmt(self.compact)
+        }
+    }
+}
+
+impl fmt::Display for Alternate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Writes a single element or key through a closure
+struct Element<F>(F);
+
+impl<F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result> fmt::Debug for Element<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+fn fmt_list<'a, T: ?Sized + 'a>(
+    mut list: fmt::DebugList<'_, '_>,
+    mut items: impl Iterator<Item = &'a T>,
+    max_items: Option<usize>,
+    element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    for item in items.by_ref().take(max_items.unwrap_or(usize::MAX)) {
+        list.entry(&Element(|f: &mut fmt::Formatter<'_>| element(item, f)));
+    }
+
+    if items.next().is_some() {
+        list.finish_non_exhaustive()
+    } else {
+        list.finish()
+    }
+}
+
+fn fmt_set<'a, T: ?Sized + 'a>(
+    mut set: fmt::DebugSet<'_, '_>,
+    mut items: impl Iterator<Item = &'a T>,
+    max_items: Option<usize>,
+    element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    for item in items.by_ref().take(max_items.unwrap_or(usize::MAX)) {
+        set.entry(&Element(|f: &mut fmt::Formatter<'_>| element(item, f)));
+    }
+
+    if items.next().is_some() {
+        set.finish_non_exhaustive()
+    } else {
+        set.finish()
+    }
+}
+
+fn fmt_map<'a, K: fmt::Debug + 'a, V: ?Sized + 'a>(
+    mut map: fmt::DebugMap<'_, '_>,
+    mut entries: impl Iterator<Item = (&'a K, &'a V)>,
+    max_items: Option<usize>,
+    element: &dyn Fn(&V, &mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    for (key, value) in entries.by_ref().take(max_items.unwrap_or(usize::MAX)) {
+        map.entry(key, &Element(|f: &mut fmt::Formatter<'_>| element(value, f)));
+    }
+
+    if entries.next().is_some() {
+        map.finish_non_exhaustive()
+    } else {
+        map.finish()
+    }
+}
+
+impl<C: Collection + ?Sized> Collection for &C {
+    type Element = C::Element;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&Self::Element, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        (**self).fmt_elements(f, max_items, element)
+    }
+}
+
+impl<T> Collection for [T] {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_list(f.debug_list(), self.iter(), max_items, element)
+    }
+}
+
+impl<T, const N: usize> Collection for [T; N] {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_list(f.debug_list(), self.iter(), max_items, element)
+    }
+}
+
+impl<T> Collection for Vec<T> {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_list(f.debug_list(), self.iter(), max_items, element)
+    }
+}
+
+impl<T> Collection for VecDeque<T> {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_list(f.debug_list(), self.iter(), max_items, element)
+    }
+}
+
+impl<T, S> Collection for HashSet<T, S> {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_set(f.debug_set(), self.iter(), max_items, element)
+    }
+}
+
+impl<T> Collection for BTreeSet<T> {
+    type Element = T;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_set(f.debug_set(), self.iter(), max_items, element)
+    }
+}
+
+impl<K: fmt::Debug, V, S> Collection for HashMap<K, V, S> {
+    type Element = V;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&V, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_map(f.debug_map(), self.iter(), max_items, element)
+    }
+}
+
+impl<K: fmt::Debug, V> Collection for BTreeMap<K, V> {
+    type Element = V;
+
+    fn fmt_elements(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        max_items: Option<usize>,
+        element: &dyn Fn(&V, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        fmt_map(f.debug_map(), self.iter(), max_items, element)
+    }
+}
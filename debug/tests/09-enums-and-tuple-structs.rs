@@ -0,0 +1,47 @@
+// Besides structs with named fields, generate the impl for tuple structs, unit
+// structs and enums. Each variant of an enum is written the same way as a
+// struct of the same shape would be:
+//
+//   - named fields through `debug_struct`,
+//   - unnamed fields through `debug_tuple`,
+//   - no fields at all as just the name of the variant.
+//
+// The #[debug = "..."] attribute applies to every kind of field, including the
+// fields of tuple variants.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UserId(#[debug = "#{}"] u64);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+#[derive(CustomDebug)]
+pub enum Error<T> {
+    NotFound { id: UserId, #[debug = "0x{:02x}"] code: u8 },
+    Io(String, #[debug = "0b{:04b}"] u8),
+    Other(T),
+    Timeout,
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(7)), "UserId(#7)");
+    assert_eq!(format!("{:?}", Marker), "Marker");
+
+    let error: Error<()> = Error::NotFound { id: UserId(1), code: 10 };
+    assert_eq!(format!("{:?}", error), "NotFound { id: UserId(#1), code: 0x0a }");
+
+    let error: Error<()> = Error::Io("denied".to_owned(), 5);
+    assert_eq!(format!("{:?}", error), r#"Io("denied", 0b0101)"#);
+
+    let error: Error<bool> = Error::Other(true);
+    assert_eq!(format!("{:?}", error), "Other(true)");
+
+    let error: Error<()> = Error::Timeout;
+    assert_eq!(format!("{:?}", error), "Timeout");
+    assert_eq!(format!("{:#?}", error), "Timeout");
+}
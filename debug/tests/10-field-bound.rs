@@ -0,0 +1,51 @@
+// A #[debug(bound = "...")] attribute on a field substitutes only the bounds
+// that would be inferred from that field's type, leaving the bounds inferred
+// from the other fields in place.
+//
+// The impl generated for Wrapper<T, U> below needs to come out as:
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, String>>();
+
+    let wrapper = Wrapper::<Id, bool> {
+        field: Field { values: vec![1, 2] },
+        normal: true,
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        "Wrapper { field: Field { values: [1, 2] }, normal: true }",
+    );
+}
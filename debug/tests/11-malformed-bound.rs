@@ -0,0 +1,18 @@
+// A bound that is not a valid list of where predicates is reported on the
+// attribute's whole string literal, along with what the parser expected.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "where T: Debug")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Other<T> {
+    #[debug(bound = "T Debug")]
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+// Type parameters can appear anywhere inside of a field's type, not only as
+// the entire type or inside of a Vec. Walk each field type recursively and
+// collect every type parameter and associated type projection it uses, apart
+// from the ones that only appear inside of a PhantomData.
+//
+// The impl generated for Fields below needs to come out as:
+//
+//     impl<'a, T: Iterator, K, V, A, B, C, P> Debug for Fields<'a, T, K, V, A, B, C, P>
+//     where
+//         A: Debug,
+//         K: Debug,
+//         V: Debug,
+//         T::Item: Debug,
+//         B: Debug,
+//         C: Debug,
+//     {...}
+//
+// so that it is still implemented when `T` and `P` do not implement Debug.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub struct Fields<'a, T: Iterator, K, V, A, B, C, P> {
+    boxed: Box<A>,
+    reference: &'a A,
+    map: HashMap<K, V>,
+    item: Option<T::Item>,
+    tuple: (B, [C; 2]),
+    marker: PhantomData<&'a (T, P)>,
+    function: fn(P) -> P,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug.
+    struct NotDebug;
+
+    assert_debug::<Fields<std::vec::IntoIter<u8>, u8, u8, u8, u8, u8, NotDebug>>();
+    assert_debug::<Fields<std::iter::Empty<String>, u8, u8, u8, u8, u8, NotDebug>>();
+}
@@ -0,0 +1,67 @@
+// Fields such as large buffers or internal caches can be left out of the
+// output with #[debug(skip)], or only when a predicate holds with
+// #[debug(skip_if = "...")]. The predicate is called with a reference to the
+// field, so paths such as `Option::is_none` can be used directly.
+//
+// Whenever a field was left out, the output ends with `..` through
+// `finish_non_exhaustive()`, so the reader can tell something was hidden.
+// Skipped fields do not need to implement Debug at all.
+
+use derive_debug::CustomDebug;
+
+pub struct Cache;
+
+#[derive(CustomDebug)]
+pub struct Connection {
+    host: String,
+    #[debug(skip)]
+    cache: Cache,
+    port: u16,
+}
+
+#[derive(CustomDebug)]
+pub struct Request {
+    path: String,
+    #[debug(skip_if = "Option::is_none")]
+    timeout: Option<u64>,
+    #[debug(skip_if = "|body: &Vec<u8>| body.is_empty()")]
+    body: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Received(#[debug(skip)] Vec<u8>, u16),
+    Closed,
+}
+
+fn main() {
+    let connection = Connection {
+        host: "localhost".to_owned(),
+        cache: Cache,
+        port: 80,
+    };
+    assert_eq!(
+        format!("{:?}", connection),
+        r#"Connection { host: "localhost", port: 80, .. }"#,
+    );
+
+    let request = Request {
+        path: "/".to_owned(),
+        timeout: None,
+        body: vec![],
+    };
+    assert_eq!(format!("{:?}", request), r#"Request { path: "/", .. }"#);
+
+    let request = Request {
+        path: "/".to_owned(),
+        timeout: Some(5),
+        body: vec![1],
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { path: "/", timeout: Some(5), body: [1] }"#,
+    );
+
+    assert_eq!(format!("{:?}", Event::Received(vec![0; 4096], 7)), "Received(7, ..)");
+    assert_eq!(format!("{:?}", Event::Closed), "Closed");
+}
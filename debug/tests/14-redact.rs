@@ -0,0 +1,55 @@
+// Secrets such as passwords or tokens should never end up in logs. A field
+// marked with #[debug(redact)] is printed as a fixed `<redacted>` placeholder
+// and its own Debug impl is never called, so it does not even need one. With
+// #[debug(redact = "len")] only the length of a String, Vec or slice is shown.
+//
+// The redaction applies the same way to the pretty printed `{:#?}` output.
+
+use derive_debug::CustomDebug;
+
+pub struct Token(String);
+
+#[derive(CustomDebug)]
+pub struct Credentials<'a> {
+    user: String,
+    #[debug(redact)]
+    token: Token,
+    #[debug(redact = "len")]
+    password: String,
+    #[debug(redact = "len")]
+    salt: &'a [u8],
+}
+
+#[derive(CustomDebug)]
+pub struct Keys<K>(#[debug(redact = "len")] Vec<K>);
+
+pub struct NotDebug;
+
+fn main() {
+    let credentials = Credentials {
+        user: "admin".to_owned(),
+        token: Token("0123456789".to_owned()),
+        password: "hunter2".to_owned(),
+        salt: b"salt",
+    };
+
+    let debug = format!("{:?}", credentials);
+    assert_eq!(
+        debug,
+        r#"Credentials { user: "admin", token: <redacted>, password: <redacted, len 7>, salt: <redacted, len 4> }"#,
+    );
+
+    let pretty = format!("{:#?}", credentials);
+    assert!(!pretty.contains("hunter2"));
+    assert!(!pretty.contains("0123456789"));
+    assert_eq!(
+        pretty,
+        "Credentials {\n    user: \"admin\",\n    token: <redacted>,\n    password: <redacted, len 7>,\n    salt: <redacted, len 4>,\n}",
+    );
+
+    let _ = credentials.token.0;
+
+    // Redacted fields need no Debug bound on their type
+    let keys = Keys(vec![NotDebug, NotDebug]);
+    assert_eq!(format!("{:?}", keys), "Keys(<redacted, len 2>)");
+}
@@ -0,0 +1,61 @@
+// Some renderings cannot be expressed through a format string, such as a hex
+// dump of a byte buffer. With #[debug(with = "path")] the field is written by
+// a user function taking a reference to the field and the formatter:
+//
+//     fn hexdump(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result;
+//
+// The field type itself does not need to implement Debug, and the function is
+// used for both `{:?}` and `{:#?}`.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+use std::time::Duration;
+
+mod render {
+    use std::fmt;
+    use std::time::Duration;
+
+    pub fn hexdump(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+
+    pub fn millis(duration: &Duration, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}ms", duration.as_millis())
+    }
+}
+
+pub struct Opaque<T>(T);
+
+fn opaque<T: fmt::Display>(value: &Opaque<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Opaque({})", value.0)
+}
+
+#[derive(CustomDebug)]
+pub struct Packet<T> {
+    #[debug(with = "render::hexdump")]
+    payload: Vec<u8>,
+    #[debug(with = "crate::render::millis")]
+    latency: Duration,
+    #[debug(with = "opaque::<T>", bound = "T: fmt::Display")]
+    tag: Opaque<T>,
+}
+
+fn main() {
+    let packet = Packet {
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+        latency: Duration::from_millis(12),
+        tag: Opaque(7),
+    };
+
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { payload: deadbeef, latency: 12ms, tag: Opaque(7) }",
+    );
+    assert_eq!(
+        format!("{:#?}", packet),
+        "Packet {\n    payload: deadbeef,\n    latency: 12ms,\n    tag: Opaque(7),\n}",
+    );
+}
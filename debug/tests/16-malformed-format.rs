@@ -0,0 +1,66 @@
+// The string in #[debug = "..."] is checked while the derive expands. It has
+// to consume the field as its one and only argument, and every placeholder
+// has to be well formed. Mistakes are reported on the attribute's literal
+// rather than somewhere inside the generated impl. The same goes for the
+// alternate format of #[debug(alt = "...")], which also needs a format string
+// to fall back to under {:?}.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct NoPlaceholder {
+    #[debug = "bitmask"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct TwoPlaceholders {
+    #[debug = "{:?} and {:?}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct UnknownTrait {
+    #[debug = "0b{:q}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct NamedArgument {
+    #[debug = "{f:?}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Unterminated {
+    #[debug = "{:08b"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct UnmatchedBrace {
+    #[debug = "{:08b} }"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct MalformedAlt {
+    #[debug(fmt = "{:?}", alt = "{:#?")]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct AltWithoutFormat {
+    #[debug(alt = "{:#?}")]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Valid {
+    #[debug = "{{{0:>#10x?}}} {0:.2e} {:+}"]
+    value: f32,
+    #[debug = "{:-^0$}"]
+    width: usize,
+}
+
+fn main() {}
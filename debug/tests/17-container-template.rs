@@ -0,0 +1,84 @@
+// Small value types read better as `(1, 2)` or `10.0.0.1:443` than through
+// `debug_struct`. A #[debug(fmt = "...")] on the struct, or on an enum
+// variant, replaces the whole output with a template. Fields are referred to
+// by name, or by position in a tuple struct, and each placeholder picks the
+// trait the field is written with: `{x}` uses Display, `{x:?}` Debug, `{x:x}`
+// LowerHex and so on.
+//
+// A field with its own formatting, such as #[debug = "..."], #[debug(redact)]
+// or #[debug(with = "...")], is written that way wherever the template uses
+// it. The bounds inferred for a generic field follow the placeholder's trait.
+
+use derive_debug::CustomDebug;
+use std::fmt::Display;
+use std::net::Ipv4Addr;
+
+#[derive(CustomDebug)]
+#[debug(fmt = "({x}, {y})")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{0}:{1}")]
+pub struct Endpoint(Ipv4Addr, u16);
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{name:?} flags={flags} {{{token}}}")]
+pub struct Session {
+    name: String,
+    #[debug = "0b{:04b}"]
+    flags: u8,
+    #[debug(redact)]
+    token: String,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "<{value}>")]
+pub struct Tagged<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug(fmt = "circle r={radius:.1}")]
+    Circle { radius: f64 },
+    #[debug(fmt = "empty")]
+    Empty,
+    Square(u32),
+}
+
+pub struct DisplayOnly;
+
+impl Display for DisplayOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("display only")
+    }
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Point { x: 1, y: -2 }), "(1, -2)");
+    assert_eq!(
+        format!("{:?}", Endpoint(Ipv4Addr::new(10, 0, 0, 1), 443)),
+        "10.0.0.1:443",
+    );
+
+    let session = Session {
+        name: "admin".to_owned(),
+        flags: 5,
+        token: "secret".to_owned(),
+    };
+    assert_eq!(
+        format!("{:?}", session),
+        r#""admin" flags=0b0101 {<redacted>}"#,
+    );
+
+    // `T` only needs Display, which is what the template writes it with
+    let tagged = Tagged { value: DisplayOnly };
+    assert_eq!(format!("{:?}", tagged), "<display only>");
+
+    assert_eq!(format!("{:?}", Shape::Circle { radius: 1.25 }), "circle r=1.2");
+    assert_eq!(format!("{:?}", Shape::Empty), "empty");
+    assert_eq!(format!("{:?}", Shape::Square(3)), "Square(3)");
+}
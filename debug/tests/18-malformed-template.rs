@@ -0,0 +1,39 @@
+// Every placeholder of a #[debug(fmt = "...")] template has to name a field
+// of the struct or variant it is placed on, and that field cannot be skipped.
+// Mistakes are reported on the template's literal.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(fmt = "({x}, {z})")]
+pub struct UnknownField {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{0}:{2}")]
+pub struct OutOfRange(u32, u16);
+
+#[derive(CustomDebug)]
+#[debug(fmt = "({}, {})")]
+pub struct Positional {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{name} {cache:?}")]
+pub struct Skipped {
+    name: String,
+    #[debug(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{}")]
+pub enum OnEnum {
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,102 @@
+// Short Rust field names can be shown under the names operators know from the
+// protocol with #[debug(rename = "...")].
+//
+// The layout of the output is picked with #[debug(style = "...")] on the
+// struct or enum, or on a single variant:
+//
+//     struct   ->  Name { a: 1, b: 2 }     through debug_struct
+//     tuple    ->  Name(1, 2)              through debug_tuple
+//     map      ->  {"a": 1, "b": 2}        through debug_map
+//     compact  ->  Name a=1 b=2            on one line, even under {:#?}
+//
+// Without a style, structs with named fields use `struct` and tuple structs
+// use `tuple` as before. Fields without a name are named after their position
+// in the styles that need a name. A skipped field ends the output with `..`
+// in every style.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Header {
+    #[debug(rename = "sequence_number")]
+    seq: u32,
+    #[debug(rename = "type")]
+    ty: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "tuple")]
+pub struct Version {
+    major: u8,
+    minor: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "map")]
+pub struct Headers {
+    #[debug(rename = "content-type")]
+    content_type: &'static str,
+    #[debug(skip)]
+    raw: Vec<u8>,
+    #[debug(rename = "content-length")]
+    content_length: usize,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "map")]
+pub struct Query {
+    q: &'static str,
+    #[debug(skip_if = "Option::is_none")]
+    page: Option<u32>,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "compact")]
+pub struct Span {
+    start: usize,
+    end: usize,
+    #[debug(skip_if = "Option::is_none")]
+    label: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "compact")]
+pub enum Frame {
+    Data(u32, #[debug(rename = "len")] usize),
+    #[debug(style = "struct")]
+    Ping { id: u64 },
+    Close,
+}
+
+fn main() {
+    let header = Header { seq: 7, ty: 2 };
+    assert_eq!(format!("{:?}", header), "Header { sequence_number: 7, type: 2 }");
+
+    assert_eq!(format!("{:?}", Version { major: 1, minor: 2 }), "Version(1, 2)");
+
+    let headers = Headers {
+        content_type: "text/plain",
+        raw: vec![],
+        content_length: 12,
+    };
+    assert_eq!(
+        format!("{:?}", headers),
+        r#"{"content-type": "text/plain", "content-length": 12, ..}"#,
+    );
+    let _ = headers.raw;
+
+    let query = Query { q: "rust", page: None };
+    assert_eq!(format!("{:?}", query), r#"{"q": "rust", ..}"#);
+    let query = Query { q: "rust", page: Some(2) };
+    assert_eq!(format!("{:?}", query), r#"{"q": "rust", "page": Some(2)}"#);
+
+    let span = Span { start: 1, end: 4, label: None };
+    assert_eq!(format!("{:?}", span), "Span start=1 end=4 ..");
+    assert_eq!(format!("{:#?}", span), "Span start=1 end=4 ..");
+    let span = Span { start: 1, end: 4, label: Some("x") };
+    assert_eq!(format!("{:#?}", span), r#"Span start=1 end=4 label=Some("x")"#);
+
+    assert_eq!(format!("{:?}", Frame::Data(3, 512)), "Data 0=3 len=512");
+    assert_eq!(format!("{:?}", Frame::Ping { id: 9 }), "Ping { id: 9 }");
+    assert_eq!(format!("{:?}", Frame::Close), "Close");
+}
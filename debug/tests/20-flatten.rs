@@ -0,0 +1,95 @@
+// A struct embedding a common header shows up as `header: Header { .. }`.
+// With #[debug(flatten)] the fields of the header are spliced into the outer
+// debug_struct output instead, as if they were declared in the outer struct.
+//
+// CustomDebug implements the derive_debug::DebugFlatten trait next to Debug
+// for this, writing the same fields with the same attributes. A type can also
+// implement DebugFlatten by hand. When the flattened value leaves out some of
+// its fields, the outer output ends with `..`.
+
+use derive_debug::{CustomDebug, DebugFlatten};
+use std::fmt;
+
+#[derive(CustomDebug)]
+pub struct Header {
+    version: u8,
+    #[debug(rename = "len")]
+    length: u16,
+}
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(flatten)]
+    header: Header,
+    payload: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Meta<T> {
+    id: T,
+    #[debug(skip_if = "Option::is_none")]
+    trace: Option<u64>,
+}
+
+#[derive(CustomDebug)]
+pub enum Message<T> {
+    Request {
+        #[debug(flatten)]
+        meta: Meta<T>,
+        path: &'static str,
+    },
+    Ack(u32),
+}
+
+pub struct Timestamp(u64);
+
+impl DebugFlatten for Timestamp {
+    fn flatten_fields(&self, debug_struct: &mut fmt::DebugStruct<'_, '_>) -> bool {
+        debug_struct.field("secs", &(self.0 / 1000));
+        debug_struct.field("millis", &(self.0 % 1000));
+        true
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Event {
+    name: &'static str,
+    #[debug(flatten)]
+    at: Timestamp,
+}
+
+fn main() {
+    let packet = Packet {
+        header: Header { version: 1, length: 3 },
+        payload: vec![1, 2, 3],
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { version: 1, len: 3, payload: [1, 2, 3] }",
+    );
+
+    let request = Message::Request {
+        meta: Meta { id: "a1", trace: None },
+        path: "/",
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { id: "a1", path: "/", .. }"#,
+    );
+
+    let request = Message::Request {
+        meta: Meta { id: 7, trace: Some(9) },
+        path: "/",
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { id: 7, trace: Some(9), path: "/" }"#,
+    );
+    assert_eq!(format!("{:?}", Message::<u8>::Ack(2)), "Ack(2)");
+
+    let event = Event { name: "tick", at: Timestamp(1500) };
+    assert_eq!(
+        format!("{:#?}", event),
+        "Event {\n    name: \"tick\",\n    secs: 1,\n    millis: 500,\n}",
+    );
+}
@@ -0,0 +1,92 @@
+// Loggers that emit structured records want the fields of a value one by one
+// rather than a single Debug string. With #[debug(visitor)] on the container,
+// CustomDebug also implements derive_debug::DebugFields, whose visit_fields
+// method passes each field to a derive_debug::FieldVisitor:
+//
+//     pub trait FieldVisitor {
+//         fn field(&mut self, name: &str, value: &dyn fmt::Debug);
+//     }
+//
+// The same rename, skip, skip_if, redact, with and format attributes as for
+// the Debug impl apply, so no second derive is needed. A flattened field
+// passes its own fields to the visitor.
+
+use derive_debug::{CustomDebug, DebugFields, FieldVisitor};
+use std::fmt;
+
+#[derive(Default)]
+struct Record {
+    entries: Vec<String>,
+}
+
+impl FieldVisitor for Record {
+    fn field(&mut self, name: &str, value: &dyn fmt::Debug) {
+        self.entries.push(format!("{}={:?}", name, value));
+    }
+}
+
+#[derive(CustomDebug)]
+#[debug(visitor)]
+pub struct Peer {
+    host: String,
+    port: u16,
+}
+
+#[derive(CustomDebug)]
+#[debug(visitor)]
+pub struct Login<T> {
+    #[debug(flatten)]
+    peer: Peer,
+    #[debug(rename = "user_id")]
+    user: T,
+    #[debug(redact)]
+    password: String,
+    #[debug = "0x{:02x}"]
+    flags: u8,
+    #[debug(skip)]
+    attempts: u32,
+    #[debug(skip_if = "Option::is_none")]
+    reason: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(visitor)]
+pub enum Outcome {
+    Success,
+    Failure(u16),
+}
+
+fn record(value: &dyn DebugFields) -> Vec<String> {
+    let mut record = Record::default();
+    value.visit_fields(&mut record);
+    record.entries
+}
+
+fn main() {
+    let login = Login {
+        peer: Peer {
+            host: "db".to_owned(),
+            port: 5432,
+        },
+        user: 42,
+        password: "hunter2".to_owned(),
+        flags: 10,
+        attempts: 3,
+        reason: None,
+    };
+
+    assert_eq!(
+        record(&login),
+        [
+            r#"host="db""#,
+            "port=5432",
+            "user_id=42",
+            "password=<redacted>",
+            "flags=0x0a",
+        ],
+    );
+    let _ = login.attempts;
+
+    assert!(record(&Outcome::Success).is_empty());
+    assert_eq!(record(&Outcome::Failure(401)), ["0=401"]);
+}
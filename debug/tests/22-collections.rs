@@ -0,0 +1,83 @@
+// A format string applies to a field as a whole, so it cannot be used to
+// print the elements of a Vec<u32> in hex. With #[debug(each = "...")] the
+// format is used for every element of a slice, Vec, VecDeque, array or set,
+// and for every value of a map, whose keys keep using Debug.
+//
+// Long collections and strings can be cut short with #[debug(max_items = N)]
+// and #[debug(max_len = N)]. The elements or characters past the limit are
+// left out, and the output ends with `..` to tell that it was cut. A generic
+// field with max_len only needs to be readable as a string, through
+// AsRef<str>, rather than implement Debug.
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeMap;
+
+#[derive(CustomDebug)]
+pub struct Registers<'a> {
+    #[debug(each = "{:#06x}")]
+    words: Vec<u32>,
+    #[debug(each = "{:02x}")]
+    mac: [u8; 6],
+    #[debug(each = "{:b}", max_items = 2)]
+    masks: &'a [u8],
+    #[debug(each = "{:.1}")]
+    readings: BTreeMap<&'static str, f64>,
+}
+
+#[derive(CustomDebug)]
+pub struct Log<T> {
+    #[debug(max_items = 3)]
+    lines: Vec<T>,
+    #[debug(max_len = 5)]
+    message: String,
+    #[debug(max_len = 5)]
+    short: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Truncated<T>(#[debug(max_len = 3)] T);
+
+// Implements AsRef<str> only
+pub struct Label(&'static str);
+
+impl AsRef<str> for Label {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+fn main() {
+    let mut readings = BTreeMap::new();
+    readings.insert("temp", 21.54);
+    let registers = Registers {
+        words: vec![1, 0xbeef],
+        mac: [0, 0x1b, 0x44, 0x11, 0x3a, 0xb7],
+        masks: &[1, 2, 4, 8],
+        readings,
+    };
+    assert_eq!(
+        format!("{:?}", registers),
+        "Registers { \
+         words: [0x0001, 0xbeef], \
+         mac: [00, 1b, 44, 11, 3a, b7], \
+         masks: [1, 10, ..], \
+         readings: {\"temp\": 21.5} }",
+    );
+
+    let log = Log {
+        lines: vec![1, 2, 3, 4, 5],
+        message: "connection reset by peer".to_owned(),
+        short: "ok",
+    };
+    assert_eq!(
+        format!("{:?}", log),
+        r#"Log { lines: [1, 2, 3, ..], message: "conne".., short: "ok" }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", log),
+        "Log {\n    lines: [\n        1,\n        2,\n        3,\n        ..\n    ],\n    \
+         message: \"conne\"..,\n    short: \"ok\",\n}",
+    );
+
+    assert_eq!(format!("{:?}", Truncated(Label("abcdef"))), r#"Truncated("abc"..)"#);
+}
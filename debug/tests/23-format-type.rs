@@ -0,0 +1,65 @@
+// Annotating every byte field of a packet struct gets repetitive. With
+// #[debug(format_type(u8 = "...", ...))] on the struct or enum, a format
+// string is given once per type name and used for every field of that type.
+// A preset for `u8` also formats each element of `[u8; N]`, `&[u8]`,
+// `Vec<u8>` and `VecDeque<u8>` fields.
+//
+// Types are matched by name, as macros cannot resolve them, so `Duration`
+// also matches a field declared as `std::time::Duration`. A field with its own
+// format string, or with another option saying how it is written, keeps it.
+
+use derive_debug::CustomDebug;
+use std::time::Duration;
+
+#[derive(CustomDebug)]
+#[debug(format_type(u8 = "{:#04x}", Duration = "{:.1?}"))]
+pub struct Packet<'a> {
+    kind: u8,
+    checksum: [u8; 2],
+    payload: Vec<u8>,
+    trailer: &'a [u8],
+    timeout: std::time::Duration,
+    #[debug = "{}"]
+    ttl: u8,
+    #[debug(max_items = 1)]
+    options: Vec<u8>,
+    id: u16,
+}
+
+#[derive(CustomDebug)]
+#[debug(format_type(u16 = "{:04x}"))]
+pub enum Frame {
+    Data { stream: u16, flags: u8 },
+    Reset(u16),
+}
+
+fn main() {
+    let packet = Packet {
+        kind: 1,
+        checksum: [0xab, 0xcd],
+        payload: vec![0, 255],
+        trailer: &[16],
+        timeout: Duration::from_millis(1520),
+        ttl: 64,
+        options: vec![1, 2],
+        id: 7,
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { \
+         kind: 0x01, \
+         checksum: [0xab, 0xcd], \
+         payload: [0x00, 0xff], \
+         trailer: [0x10], \
+         timeout: 1.5s, \
+         ttl: 64, \
+         options: [1, ..], \
+         id: 7 }",
+    );
+
+    assert_eq!(
+        format!("{:?}", Frame::Data { stream: 300, flags: 1 }),
+        "Data { stream: 012c, flags: 1 }",
+    );
+    assert_eq!(format!("{:?}", Frame::Reset(16)), "Reset(0010)");
+}
@@ -0,0 +1,36 @@
+// Only attributes whose path is `debug` are looked at, so doc comments and
+// attributes meant for other tools can come before or after them.
+//
+// A field's format string can be given either as #[debug = "..."] or in the
+// list form as #[debug(fmt = "...")], and the options of a field can be
+// spread over several #[debug(...)] attributes.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+/// A span of bytes in the input
+#[debug(style = "struct")]
+pub struct Span {
+    /// The first byte
+    #[allow(dead_code)]
+    #[debug = "@{}"]
+    start: usize,
+    #[debug(fmt = "{:#x}")]
+    /// One past the last byte
+    end: usize,
+    #[debug(rename = "name")]
+    #[debug(skip_if = "Option::is_none")]
+    label: Option<&'static str>,
+}
+
+fn main() {
+    let span = Span {
+        start: 4,
+        end: 16,
+        label: Some("token"),
+    };
+    assert_eq!(
+        format!("{:?}", span),
+        r#"Span { start: @4, end: 0x10, name: Some("token") }"#,
+    );
+}
@@ -0,0 +1,71 @@
+// Every key given in a #[debug(...)] attribute has to be one this derive
+// knows, and each of them may only be given once per struct, variant or
+// field. Anything else is reported on the offending key rather than being
+// silently ignored, and code given as a string, such as the predicate of
+// skip_if, is reported on that string when it does not parse.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UnknownFieldKey {
+    #[debug(skipp)]
+    a: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "")]
+#[debug(bound = "")]
+pub struct DuplicateContainerKey<T> {
+    a: T,
+}
+
+#[derive(CustomDebug)]
+pub struct DuplicateFormat {
+    #[debug = "{}"]
+    #[debug(fmt = "{:?}")]
+    a: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct DuplicateFieldKey {
+    #[debug(rename = "b", skip_if = "Option::is_none", rename = "c")]
+    a: Option<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(format_type(u8 = "{:x}", u8 = "{:o}"))]
+pub struct DuplicateFormatType {
+    a: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct ConflictingOptions {
+    #[debug(redact, with = "std::fmt::Debug::fmt")]
+    a: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct BareAttribute {
+    #[debug]
+    a: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum UnknownVariantKey {
+    #[debug(bound = "")]
+    A,
+}
+
+#[derive(CustomDebug)]
+pub struct MalformedPredicate {
+    #[debug(skip_if = "Option::is_none ||")]
+    a: Option<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct MalformedFunction {
+    #[debug(with = "fmt::")]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+// Lifetime and const parameters are kept on the generated impls as they are
+// declared, and only type parameters are ever bounded. A field such as
+// `&'a [T; N]` needs exactly `T: Debug`: the reference and the array length
+// do not add anything, and `N` is not a type.
+//
+// Trait object fields such as `Box<dyn Debug + 'a>` are written through the
+// Debug impl of the trait object, without any bound on the parameters of the
+// struct.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub struct Frame<'a, const N: usize, T> {
+    samples: &'a [T; N],
+    window: [u8; N],
+}
+
+#[derive(CustomDebug)]
+pub struct Labeled<'a, 'b: 'a, T: ?Sized, const WIDTH: usize = 4> {
+    label: &'a &'b str,
+    value: &'a T,
+}
+
+#[derive(CustomDebug)]
+pub struct Erased<'a, M> {
+    inner: Box<dyn Debug + 'a>,
+    #[debug(skip)]
+    marker: PhantomData<M>,
+}
+
+pub struct NotDebug;
+
+fn assert_debug<T: Debug>() {}
+
+fn main() {
+    let samples = [1.5, 2.5];
+    let frame: Frame<2, f64> = Frame {
+        samples: &samples,
+        window: [0, 1],
+    };
+    assert_eq!(
+        format!("{:?}", frame),
+        "Frame { samples: [1.5, 2.5], window: [0, 1] }",
+    );
+
+    let name = "id";
+    let labeled: Labeled<str> = Labeled {
+        label: &name,
+        value: "x1",
+    };
+    assert_eq!(format!("{:?}", labeled), r#"Labeled { label: "id", value: "x1" }"#);
+
+    // Nothing is required from `M`, which is only used in a skipped field
+    let erased: Erased<NotDebug> = Erased {
+        inner: Box::new(7),
+        marker: PhantomData,
+    };
+    assert_eq!(format!("{:?}", erased), "Erased { inner: 7, .. }");
+    assert_debug::<Erased<'static, NotDebug>>();
+    let _ = erased.marker;
+}
@@ -0,0 +1,98 @@
+// User-facing error and status types want a Display impl that follows the
+// same rules as their Debug one. The CustomDisplay derive writes a struct
+// through a #[display("...")] template, and an enum through a template on
+// each of its variants, with unit variants falling back to their name.
+//
+// Templates work exactly as #[debug(fmt = "...")] does: `{host}` writes the
+// field with Display, `{host:?}` with Debug, and a field's own #[debug(...)]
+// options such as redact or with apply wherever the template uses it. Bounds
+// on generic fields are inferred from the trait of each placeholder, and
+// #[display(bound = "...")] replaces them.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+use std::fmt::{self, Display};
+
+#[derive(CustomDebug, CustomDisplay)]
+#[display("{host}:{port}")]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+    #[debug(skip)]
+    retries: u32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0} at {1:?}")]
+pub struct Located(&'static str, (u32, u32));
+
+#[derive(CustomDisplay)]
+pub enum Status {
+    Ready,
+    #[display("busy for {0}s")]
+    Busy(u64),
+    #[display("failed: {reason} (token {token})")]
+    Failed {
+        reason: String,
+        #[debug(redact)]
+        token: String,
+    },
+}
+
+#[derive(CustomDisplay)]
+#[display("<{value}>")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+// `T` is only written through a helper, so the inferred bound is replaced
+#[derive(CustomDisplay)]
+#[display("{value}", bound = "")]
+pub struct Counted<T> {
+    #[debug(with = "count")]
+    value: Vec<T>,
+}
+
+fn count<T>(value: &Vec<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} items", value.len())
+}
+
+pub struct DisplayOnly;
+
+impl Display for DisplayOnly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("display only")
+    }
+}
+
+pub struct Opaque;
+
+fn main() {
+    let endpoint = Endpoint {
+        host: "example.com".to_owned(),
+        port: 443,
+        retries: 3,
+    };
+    assert_eq!(endpoint.to_string(), "example.com:443");
+    assert_eq!(
+        format!("{:?}", endpoint),
+        r#"Endpoint { host: "example.com", port: 443, .. }"#,
+    );
+
+    assert_eq!(Located("origin", (0, 1)).to_string(), "origin at (0, 1)");
+
+    assert_eq!(Status::Ready.to_string(), "Ready");
+    assert_eq!(Status::Busy(5).to_string(), "busy for 5s");
+    let failed = Status::Failed {
+        reason: "timeout".to_owned(),
+        token: "secret".to_owned(),
+    };
+    assert_eq!(failed.to_string(), "failed: timeout (token <redacted>)");
+
+    // `T` only needs Display, which is what the template writes it with
+    assert_eq!(Wrapper { value: DisplayOnly }.to_string(), "<display only>");
+
+    let counted = Counted {
+        value: vec![Opaque, Opaque],
+    };
+    assert_eq!(counted.to_string(), "2 items");
+}
@@ -0,0 +1,40 @@
+// CustomDisplay has no field layout to fall back on, so a struct or a
+// variant with fields has to give a template. Templates are given on each
+// variant of an enum rather than on the enum itself, and fields hidden with
+// #[debug(skip)] cannot be written by them.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+pub struct MissingTemplate {
+    a: u8,
+}
+
+#[derive(CustomDisplay)]
+#[display("{a}")]
+pub enum TemplateOnEnum {
+    A { a: u8 },
+}
+
+#[derive(CustomDisplay)]
+pub enum MissingVariantTemplate {
+    A,
+    B(u8),
+}
+
+#[derive(CustomDisplay)]
+#[display("{a} {secret}")]
+pub struct SkippedField {
+    a: u8,
+    #[debug(skip)]
+    secret: u8,
+}
+
+#[derive(CustomDisplay)]
+#[display("{a}")]
+#[display("{a}!")]
+pub struct RepeatedTemplate {
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,83 @@
+// Derived facts such as a length or an expiry flag are often more useful
+// than the raw fields they come from. A #[debug(extra(name = "...", expr =
+// "..."))] on the struct, or on an enum variant, appends an entry named
+// `name` whose value is the Debug of `expr`. The expression is compiled as
+// part of the generated impl, where it can refer to `self`, and the attribute
+// can be repeated to add several entries.
+
+use derive_debug::{CustomDebug, DebugFields, FieldVisitor};
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len", expr = "self.items.len()"))]
+#[debug(extra(name = "is_empty", expr = "self.items.is_empty()"), visitor)]
+pub struct Queue {
+    items: Vec<u32>,
+    #[debug(skip)]
+    capacity: usize,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "compact")]
+pub enum Token {
+    #[debug(extra(name = "expired", expr = "self.is_expired()"))]
+    Session { ttl: u32 },
+    #[debug(extra(name = "kind", expr = "\"anonymous\""))]
+    Anonymous,
+}
+
+impl Token {
+    fn is_expired(&self) -> bool {
+        matches!(self, Token::Session { ttl: 0 })
+    }
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "map", extra(name = "sum", expr = "self.0 + self.1"))]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Outer {
+    id: u8,
+    #[debug(flatten)]
+    queue: Queue,
+}
+
+#[derive(Default)]
+struct Collect(Vec<String>);
+
+impl FieldVisitor for Collect {
+    fn field(&mut self, name: &str, value: &dyn Debug) {
+        self.0.push(format!("{}={:?}", name, value));
+    }
+}
+
+fn main() {
+    let queue = Queue {
+        items: vec![1, 2],
+        capacity: 8,
+    };
+    assert_eq!(queue.capacity, 8);
+    assert_eq!(
+        format!("{:?}", queue),
+        "Queue { items: [1, 2], len: 2, is_empty: false, .. }",
+    );
+
+    let mut collect = Collect::default();
+    queue.visit_fields(&mut collect);
+    assert_eq!(collect.0, ["items=[1, 2]", "len=2", "is_empty=false"]);
+
+    assert_eq!(
+        format!("{:?}", Token::Session { ttl: 0 }),
+        "Session ttl=0 expired=true",
+    );
+    assert_eq!(format!("{:?}", Token::Anonymous), r#"Anonymous kind="anonymous""#);
+
+    assert_eq!(format!("{:?}", Pair(1, 2)), r#"{"0": 1, "1": 2, "sum": 3}"#);
+
+    let outer = Outer { id: 7, queue };
+    assert_eq!(
+        format!("{:?}", outer),
+        "Outer { id: 7, items: [1, 2], len: 2, is_empty: false, .. }",
+    );
+}
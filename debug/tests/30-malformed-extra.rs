@@ -0,0 +1,43 @@
+// An extra entry needs both its name and its expression, and its name has to
+// differ from the other extra entries. Templates and `debug_tuple` output
+// have no place for named entries, so extra cannot be combined with them.
+// The expression is type-checked like any other code in the generated impl.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len"))]
+pub struct MissingExpr {
+    items: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len", expr = "self.items.len()"))]
+#[debug(extra(name = "len", expr = "self.items.capacity()"))]
+pub struct DuplicateName {
+    items: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{items:?}", extra(name = "len", expr = "self.items.len()"))]
+pub struct WithTemplate {
+    items: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len", expr = "self.0.len()"))]
+pub struct Tuple(Vec<u8>);
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len", expr = "self.items.len() +"))]
+pub struct MalformedExpr {
+    items: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(extra(name = "len", expr = "self.missing.len()"))]
+pub struct UnknownField {
+    items: Vec<u8>,
+}
+
+fn main() {}
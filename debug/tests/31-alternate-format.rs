@@ -0,0 +1,53 @@
+// Pretty-printed output has room for details that the compact one leaves
+// out. A field with #[debug(fmt = "...", alt = "...")] is written with `fmt`
+// under {:?} and with `alt` under {:#?}, which is decided by the `#` flag of
+// the formatter the field is written with. `alt` can also go next to the
+// #[debug = "..."] shorthand.
+//
+// A template placeholder passes its own flags instead, so `{digest}` in a
+// container template writes `fmt` and `{digest:#}` writes `alt`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    id: u16,
+    #[debug(fmt = "{:.8}..", alt = "{}")]
+    digest: String,
+    #[debug = "{:.1}"]
+    #[debug(alt = "{:.4}")]
+    ratio: f64,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{digest} / {digest:#}")]
+pub struct Summary {
+    #[debug(fmt = "{:.4}..", alt = "{}")]
+    digest: &'static str,
+}
+
+fn main() {
+    let packet = Packet {
+        id: 7,
+        digest: "0123456789abcdef".to_owned(),
+        ratio: 0.123456,
+    };
+
+    assert_eq!(
+        format!("{:?}", packet),
+        r#"Packet { id: 7, digest: 01234567.., ratio: 0.1 }"#,
+    );
+
+    let expected = "\
+Packet {
+    id: 7,
+    digest: 0123456789abcdef,
+    ratio: 0.1235,
+}";
+    assert_eq!(format!("{:#?}", packet), expected);
+
+    let summary = Summary {
+        digest: "0123456789",
+    };
+    assert_eq!(format!("{:?}", summary), "0123.. / 0123456789");
+}
@@ -0,0 +1,65 @@
+// Wrappers such as `struct UserId(u64)` read best as just their inner value.
+// With #[debug(transparent)] on a struct, or on an enum variant, the Debug
+// impl delegates to the one field that is not skipped, passing the formatter
+// on so that {:#?} and the other flags still apply to it. The field's own
+// options, such as #[debug = "..."] or #[debug(redact)], are kept.
+
+use derive_debug::CustomDebug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Name {
+    value: String,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Typed<T, Unit> {
+    value: T,
+    #[debug(skip)]
+    unit: PhantomData<Unit>,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Secret(#[debug(redact)] String);
+
+#[derive(CustomDebug)]
+pub enum Id {
+    #[debug(transparent)]
+    User(UserId),
+    #[debug(transparent)]
+    Raw(#[debug = "{:#x}"] u32),
+    Anonymous,
+}
+
+pub struct NotDebug;
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:>5?}", UserId(42)), "   42");
+
+    let name = Name {
+        value: "alice".to_owned(),
+    };
+    assert_eq!(format!("{:?}", name), r#""alice""#);
+
+    // `Unit` is only used by a skipped field and needs no bound
+    let typed: Typed<Vec<u8>, NotDebug> = Typed {
+        value: vec![1, 2],
+        unit: PhantomData,
+    };
+    assert_eq!(format!("{:?}", typed), "[1, 2]");
+    assert_eq!(format!("{:#?}", typed), "[\n    1,\n    2,\n]");
+
+    assert_eq!(format!("{:?}", Secret("hunter2".to_owned())), "<redacted>");
+
+    assert_eq!(format!("{:?}", Id::User(UserId(7))), "7");
+    assert_eq!(format!("{:?}", Id::Raw(255)), "0xff");
+    assert_eq!(format!("{:?}", Id::Anonymous), "Anonymous");
+}
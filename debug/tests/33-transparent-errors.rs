@@ -0,0 +1,43 @@
+// #[debug(transparent)] needs exactly one field left to delegate to, and a
+// second field that is written gets reported. It replaces the whole layout,
+// so it cannot be combined with a template, a style or extra entries, and it
+// is given on each variant of an enum rather than on the enum itself.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct TwoFields {
+    a: u8,
+    b: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct NoField;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct AllSkipped(#[debug(skip)] u8);
+
+#[derive(CustomDebug)]
+pub enum Variant {
+    #[debug(transparent)]
+    Pair(u8, u8),
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct SkippedIf(#[debug(skip_if = "Option::is_none")] Option<u8>);
+
+#[derive(CustomDebug)]
+#[debug(transparent, style = "map")]
+pub struct WithStyle(u8);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub enum OnEnum {
+    A(u8),
+}
+
+fn main() {}
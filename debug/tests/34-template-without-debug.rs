@@ -0,0 +1,35 @@
+// A container template only requires each field to implement the trait its
+// placeholder writes it with. A field that is only ever written as `{x}`
+// therefore does not need a Debug impl at all, even though the type is not
+// generic and no bound can be left out for it. Such a type has no fields of
+// its own in the output, so it does not implement DebugFlatten either.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Display};
+
+pub struct OnlyDisplay;
+
+impl Display for OnlyDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("only display")
+    }
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "<{x}>")]
+pub struct P {
+    x: OnlyDisplay,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug(fmt = "label {0}")]
+    Label(OnlyDisplay),
+    Point { x: i32, y: i32 },
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", P { x: OnlyDisplay }), "<only display>");
+    assert_eq!(format!("{:?}", Shape::Label(OnlyDisplay)), "label only display");
+    assert_eq!(format!("{:?}", Shape::Point { x: 1, y: 2 }), "Point { x: 1, y: 2 }");
+}
@@ -0,0 +1,68 @@
+// A field with its own format string is written with the traits of that
+// string's placeholders rather than with Debug, so a generic field with
+// #[debug = "{:#x}"] needs `T: LowerHex` and nothing else. The same goes for
+// the alternate format of #[debug(alt = "...")].
+//
+// With #[debug(each = "...")] on a map, only the values go through the
+// format string. The keys are always written with Debug, which is what they
+// are bounded with.
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, LowerHex};
+
+#[derive(CustomDebug)]
+pub struct Hex<T> {
+    #[debug = "{:#x}"]
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Reading<T> {
+    #[debug(fmt = "{}", alt = "{:#x}")]
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Registers<K, V> {
+    #[debug(each = "{:#04x}")]
+    values: BTreeMap<K, V>,
+}
+
+// Implements LowerHex and Display only
+pub struct Word(u16);
+
+impl LowerHex for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+// Implements Debug and Ord only
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Register {
+    A,
+    B,
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Hex { value: Word(255) }), "Hex { value: 0xff }");
+
+    let reading = Reading { value: Word(16) };
+    assert_eq!(format!("{:?}", reading), "Reading { value: 16 }");
+    assert_eq!(format!("{:#?}", reading), "Reading {\n    value: 0x10,\n}");
+
+    let mut values = BTreeMap::new();
+    values.insert(Register::A, Word(1));
+    values.insert(Register::B, Word(42));
+    assert_eq!(
+        format!("{:?}", Registers { values }),
+        "Registers { values: {A: 0x01, B: 0x2a} }",
+    );
+}
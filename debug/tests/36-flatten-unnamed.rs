@@ -0,0 +1,71 @@
+// Flattening splices the fields of a value into its parent under their own
+// names. Tuple structs, newtypes with #[debug(transparent)] and enums with a
+// tuple variant have fields without names, which would show up as `0: ...`
+// among the parent's fields, so they do not implement DebugFlatten and a
+// #[debug(flatten)] field of such a type does not compile.
+//
+// Whether a type implements DebugFlatten is checked below through method
+// resolution, which prefers the inherent method whenever its bound holds.
+
+use derive_debug::{CustomDebug, DebugFlatten};
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub struct Name(String);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Label {
+    value: String,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    Circle { radius: u32 },
+    Square(u32),
+}
+
+#[derive(CustomDebug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct Outer {
+    #[debug(flatten)]
+    point: Point,
+    n: Name,
+}
+
+struct Probe<T>(PhantomData<T>);
+
+impl<T: DebugFlatten> Probe<T> {
+    fn flattenable(&self) -> bool {
+        true
+    }
+}
+
+trait NotFlattenable {
+    fn flattenable(&self) -> bool {
+        false
+    }
+}
+
+impl<T> NotFlattenable for Probe<T> {}
+
+fn main() {
+    assert!(!Probe::<Name>(PhantomData).flattenable());
+    assert!(!Probe::<Label>(PhantomData).flattenable());
+    assert!(!Probe::<Shape>(PhantomData).flattenable());
+    assert!(Probe::<Point>(PhantomData).flattenable());
+
+    let outer = Outer {
+        point: Point { x: 1, y: 2 },
+        n: Name("n".to_owned()),
+    };
+    assert_eq!(format!("{:?}", outer), r#"Outer { x: 1, y: 2, n: Name("n") }"#);
+    let _ = Label { value: String::new() }.value;
+    let _ = Shape::Circle { radius: 1 };
+    let _ = Shape::Square(1);
+}
@@ -0,0 +1,20 @@
+// A union does not know which of its fields holds a value, so neither
+// derive can write it. Deriving them on a union is reported on its name
+// rather than making the derive panic.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+
+#[derive(CustomDebug)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{int}")]
+pub union Word {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
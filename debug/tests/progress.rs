@@ -8,5 +8,34 @@ fn tests() {
     t.pass("tests/05-phantom-data.rs");
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enums-and-tuple-structs.rs");
+    t.pass("tests/10-field-bound.rs");
+    t.compile_fail("tests/11-malformed-bound.rs");
+    t.pass("tests/12-type-tree-bounds.rs");
+    t.pass("tests/13-skip-fields.rs");
+    t.pass("tests/14-redact.rs");
+    t.pass("tests/15-with-function.rs");
+    t.compile_fail("tests/16-malformed-format.rs");
+    t.pass("tests/17-container-template.rs");
+    t.compile_fail("tests/18-malformed-template.rs");
+    t.pass("tests/19-rename-and-style.rs");
+    t.pass("tests/20-flatten.rs");
+    t.pass("tests/21-field-visitor.rs");
+    t.pass("tests/22-collections.rs");
+    t.pass("tests/23-format-type.rs");
+    t.pass("tests/24-attribute-forms.rs");
+    t.compile_fail("tests/25-attribute-errors.rs");
+    t.pass("tests/26-lifetimes-and-const-generics.rs");
+    t.pass("tests/27-custom-display.rs");
+    t.compile_fail("tests/28-display-errors.rs");
+    t.pass("tests/29-extra-entries.rs");
+    t.compile_fail("tests/30-malformed-extra.rs");
+    t.pass("tests/31-alternate-format.rs");
+    t.pass("tests/32-transparent.rs");
+    t.compile_fail("tests/33-transparent-errors.rs");
+    t.pass("tests/34-template-without-debug.rs");
+    t.pass("tests/35-format-string-bounds.rs");
+    t.pass("tests/36-flatten-unnamed.rs");
+    t.compile_fail("tests/37-unions.rs");
 }